@@ -0,0 +1,90 @@
+//! Parse, part 1 and part 2 timings for every day, on the worked example, a generated input of the
+//! usual size and, when present, the real input.
+//!
+//! Criterion keeps the previous run under `target/criterion` and reports the change against it;
+//! use `cargo bench -- --save-baseline <name>` and `--baseline <name>` to compare against a fixed
+//! point, such as before and after a rewrite.
+#![warn(clippy::pedantic)]
+use aoc2022::{
+    day1::Day1,
+    day10::Day10,
+    day11::Day11,
+    day12::Day12,
+    day13::Day13,
+    day14::Day14,
+    day15::{self, Day15},
+    day17::Day17,
+    day2::Day2,
+    day25::Day25,
+    day3::Day3,
+    day4::Day4,
+    day5::Day5,
+    day6::Day6,
+    day7::{self, Day7},
+    day8::Day8,
+    day9::Day9,
+    generate::{self, Generate},
+    input::InputSource,
+    solution::Solution,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::fs;
+
+fn bench_input<S: Solution>(c: &mut Criterion, name: &str, data: &[u8], params: &S::Params) {
+    let mut group = c.benchmark_group(format!("day{}/{}", S::DAY, name));
+    group.sample_size(10);
+
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(data)).unwrap()));
+
+    let input = S::parse(data).unwrap();
+    group.bench_function("part1", |b| b.iter(|| S::part1(black_box(&input), params)));
+    group.bench_function("part2", |b| b.iter(|| S::part2(black_box(&input), params)));
+
+    group.finish();
+}
+
+/// Benchmarks the sample with `sample_params`, then a generated input and the real input with the
+/// default parameters.
+fn bench_day<S: Generate>(c: &mut Criterion, sample: &str, sample_params: &S::Params) {
+    let data = fs::read(sample).unwrap();
+    bench_input::<S>(c, "sample", &data, sample_params);
+
+    let generated = generate::generate::<S>(&generate::Options::default());
+    bench_input::<S>(c, "generated", generated.as_bytes(), &S::Params::default());
+
+    if let InputSource::Path(path) = InputSource::for_day(S::DAY) {
+        if let Ok(data) = fs::read(path) {
+            bench_input::<S>(c, "real", &data, &S::Params::default());
+        }
+    }
+}
+
+fn days(c: &mut Criterion) {
+    bench_day::<Day1>(c, "inputs-sample/day1", &());
+    bench_day::<Day2>(c, "inputs-sample/day2", &());
+    bench_day::<Day3>(c, "inputs-sample/day3", &());
+    bench_day::<Day4>(c, "inputs-sample/day4", &());
+    bench_day::<Day5>(c, "inputs-sample/day5", &());
+    bench_day::<Day6>(c, "inputs-sample/day6", &());
+    bench_day::<Day7>(c, "inputs-sample/day7", &day7::Params::default());
+    bench_day::<Day8>(c, "inputs-sample/day8", &());
+    bench_day::<Day9>(c, "inputs-sample/day9-sample2", &());
+    bench_day::<Day10>(c, "inputs-sample/day10-sample2", &());
+    bench_day::<Day11>(c, "inputs-sample/day11", &());
+    bench_day::<Day12>(c, "inputs-sample/day12", &());
+    bench_day::<Day13>(c, "inputs-sample/day13", &());
+    bench_day::<Day14>(c, "inputs-sample/day14", &());
+    bench_day::<Day15>(
+        c,
+        "inputs-sample/day15",
+        &day15::Params {
+            part1_y: 10,
+            part2_extent: 20,
+        },
+    );
+    bench_day::<Day17>(c, "inputs-sample/day17", &());
+    bench_day::<Day25>(c, "inputs-sample/day25", &());
+}
+
+criterion_group!(benches, days);
+criterion_main!(benches);
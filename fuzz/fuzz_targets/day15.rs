@@ -0,0 +1,16 @@
+#![no_main]
+use aoc2022::{
+    day15::{Day15, Params},
+    solution::solve_with,
+};
+use libfuzzer_sys::fuzz_target;
+
+// the worked example's search area, as the real one takes seconds per input
+const PARAMS: Params = Params {
+    part1_y: 10,
+    part2_extent: 20,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = solve_with::<Day15, _>(data, &PARAMS);
+});
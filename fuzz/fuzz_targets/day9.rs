@@ -0,0 +1,7 @@
+#![no_main]
+use aoc2022::{day9::Day9, solution::solve};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = solve::<Day9, _>(data);
+});
@@ -0,0 +1,18 @@
+#!/bin/sh
+# Copies the worked examples, and a few small generated inputs, into each fuzz target's corpus as
+# starting points for mutation.
+set -e
+cd "$(dirname "$0")"
+for target in fuzz_targets/*.rs; do
+    day=$(basename "$target" .rs)
+    mkdir -p "corpus/$day"
+    for sample in "../inputs-sample/$day" "../inputs-sample/$day"-*; do
+        if [ -f "$sample" ]; then
+            cp "$sample" "corpus/$day/"
+        fi
+    done
+    for seed in 1 2 3; do
+        cargo run --quiet --manifest-path ../Cargo.toml --bin aoc22 -- \
+            gen "${day#day}" --seed "$seed" --size 10 > "corpus/$day/gen-$seed"
+    done
+done
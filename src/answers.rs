@@ -0,0 +1,178 @@
+use crate::{error::Result, solution::Report};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+/// Where known-good answers are kept unless told otherwise.
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.toml";
+
+/// Hashes puzzle input so answers can be keyed by the exact input they were computed from.
+///
+/// This is 64-bit FNV-1a, which is stable across Rust versions unlike `DefaultHasher`.
+#[must_use]
+pub fn input_hash(data: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in data {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Loads a TOML file from `path`; a missing file gives the default.
+pub(crate) fn load_toml<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            toml::from_str(&contents).map_err(|e| io::Error::new(ErrorKind::InvalidData, e).into())
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub(crate) fn save_toml<T: Serialize>(value: &T, path: &Path) -> Result<()> {
+    let contents =
+        toml::to_string_pretty(value).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+    Ok(fs::write(path, contents)?)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Expected {
+    pub part1: String,
+    pub part2: String,
+}
+
+impl From<&Report> for Expected {
+    fn from(report: &Report) -> Self {
+        Self {
+            part1: report.part1.clone(),
+            part2: report.part2.clone(),
+        }
+    }
+}
+
+/// Known-good answers, stored as `[dayN.<input hash>]` tables in TOML.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Answers(BTreeMap<String, BTreeMap<String, Expected>>);
+
+impl Answers {
+    /// Loads answers from `path`; a missing file is treated as having no answers yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        load_toml(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_toml(self, path)
+    }
+
+    #[must_use]
+    pub fn get(&self, day: u32, hash: &str) -> Option<&Expected> {
+        self.0.get(&format!("day{}", day))?.get(hash)
+    }
+
+    pub fn insert(&mut self, day: u32, hash: &str, expected: Expected) {
+        self.0
+            .entry(format!("day{}", day))
+            .or_default()
+            .insert(hash.to_string(), expected);
+    }
+}
+
+/// Outcome of checking a fresh report against the recorded answers.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail(Vec<Mismatch>),
+    Missing,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub part: u8,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  part {}:", self.part)?;
+        for line in self.expected.lines() {
+            writeln!(f, "    - {}", line)?;
+        }
+        for line in self.actual.lines() {
+            writeln!(f, "    + {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[must_use]
+pub fn verdict(expected: Option<&Expected>, report: &Report) -> Verdict {
+    let Some(expected) = expected else {
+        return Verdict::Missing;
+    };
+
+    let mismatches: Vec<Mismatch> = [
+        (1, &expected.part1, &report.part1),
+        (2, &expected.part2, &report.part2),
+    ]
+    .into_iter()
+    .filter(|(_, expected, actual)| expected != actual)
+    .map(|(part, expected, actual)| Mismatch {
+        part,
+        expected: expected.clone(),
+        actual: actual.clone(),
+    })
+    .collect();
+
+    if mismatches.is_empty() {
+        Verdict::Pass
+    } else {
+        Verdict::Fail(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{input_hash, verdict, Answers, Expected, Mismatch, Verdict};
+    use crate::{day2::Day2, solution::run};
+
+    #[test]
+    fn hash_is_stable() {
+        assert_eq!(input_hash(b""), "cbf29ce484222325");
+        assert_eq!(input_hash(b"a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn round_trip_and_verdicts() {
+        let input = "A Y\nB X\nC Z\n";
+        let hash = input_hash(input.as_bytes());
+        let report = run::<Day2>(&mut input.as_bytes()).unwrap();
+
+        let mut answers = Answers::default();
+        assert_eq!(verdict(answers.get(2, &hash), &report), Verdict::Missing);
+
+        answers.insert(2, &hash, Expected::from(&report));
+        let answers: Answers = toml::from_str(&toml::to_string(&answers).unwrap()).unwrap();
+        assert_eq!(verdict(answers.get(2, &hash), &report), Verdict::Pass);
+        assert_eq!(answers.get(2, "0000000000000000"), None);
+
+        let wrong = Expected {
+            part1: "15".to_string(),
+            part2: "13".to_string(),
+        };
+        assert_eq!(
+            verdict(Some(&wrong), &report),
+            Verdict::Fail(vec![Mismatch {
+                part: 2,
+                expected: "13".to_string(),
+                actual: "12".to_string(),
+            }])
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A simulation that settled into a loop, found by [`find_cycle`].
+#[derive(Debug, Clone)]
+pub struct Cycle<M> {
+    /// Steps taken before the loop begins.
+    pub start: usize,
+    /// Steps it takes to come back around.
+    pub length: usize,
+    /// The measurement after each of steps `0..=start + length`.
+    history: Vec<M>,
+}
+
+impl<M> Cycle<M> {
+    /// The step before the loop came back around that looks the same as step `n`.
+    #[must_use]
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+
+    /// The measurement after step `n`, or before it had looped if `n` is beyond that.
+    #[must_use]
+    pub fn measurement(&self, n: usize) -> &M {
+        &self.history[self.equivalent_step(n)]
+    }
+
+    /// Extends `metric` out to step `n`, assuming it grows by the same amount every time around
+    /// the loop, like a running total.
+    pub fn extrapolate(&self, n: usize, metric: impl Fn(&M) -> u64) -> u64 {
+        let base = metric(self.measurement(n));
+        if n < self.start + self.length {
+            return base;
+        }
+
+        let per_cycle =
+            metric(&self.history[self.start + self.length]) - metric(&self.history[self.start]);
+        let cycles = (n - self.start) / self.length;
+        base + per_cycle * cycles as u64
+    }
+}
+
+/// Runs `state` forward with `step` until the value of `key` repeats, for at most `limit` steps.
+///
+/// `measure` records whatever should be extrapolated after each step. Either way `state` is left
+/// where the search stopped, so if no loop turns up within `limit` it has simply been run that far.
+pub fn find_cycle<S, K: Hash + Eq, M>(
+    state: &mut S,
+    limit: usize,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+    mut measure: impl FnMut(&S) -> M,
+) -> Option<Cycle<M>> {
+    let mut seen = HashMap::new();
+    let mut history = Vec::new();
+    for n in 0..=limit {
+        if n > 0 {
+            step(state);
+        }
+        history.push(measure(state));
+        if let Some(start) = seen.insert(key(state), n) {
+            return Some(Cycle {
+                start,
+                length: n - start,
+                history,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::find_cycle;
+
+    #[test]
+    fn finds_cycle() {
+        // 3, 0, 1, 2, 5, 6, 7, 0, ... so the loop starts after one step and is six long
+        let next = |v: u64| (v * v + 1) % 10;
+        let mut state = (3, 0);
+        let cycle = find_cycle(
+            &mut state,
+            100,
+            |(v, total)| {
+                *v = next(*v);
+                *total += *v;
+            },
+            |&(v, _)| v,
+            |&(_, total)| total,
+        )
+        .unwrap();
+
+        assert_eq!((cycle.start, cycle.length), (1, 6));
+        assert_eq!(cycle.equivalent_step(9), 3);
+
+        let mut brute = (3, 0);
+        for n in 0..50 {
+            assert_eq!(cycle.extrapolate(n, |&total| total), brute.1, "step {}", n);
+            brute.0 = next(brute.0);
+            brute.1 += brute.0;
+        }
+    }
+
+    #[test]
+    fn gives_up() {
+        let mut state = 0;
+        assert!(find_cycle(&mut state, 10, |v| *v += 1, |&v| v, |&v| v).is_none());
+        assert_eq!(state, 10);
+    }
+}
@@ -1,72 +1,130 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+};
+use rand::Rng;
 use std::{io::BufRead, mem};
 
 #[derive(Debug)]
-struct ElfData {
-    total_calories: u32,
+pub struct ElfData {
+    total_calories: u64,
     //calories : Vec<u32>,
 }
 
 impl ElfData {
     fn new(calories: Vec<u32>) -> Self {
         Self {
-            total_calories: calories.into_iter().sum(),
+            // a u64 can't overflow summing u32s from any input that fits in memory
+            total_calories: calories.into_iter().map(u64::from).sum(),
             //calories
         }
     }
 }
 
-fn solve(filename: &str) -> aoc::Result<(u32, u32)> {
-    let reader = aoc::file(filename)?;
+pub struct Day1;
 
-    let elves = {
+impl Solution for Day1 {
+    const DAY: u32 = 1;
+
+    type Input = Vec<ElfData>;
+    type Params = ();
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
         let mut elves: Vec<ElfData> = Vec::new();
         let mut current_elf: Vec<u32> = Vec::new();
-        for line_result in reader.lines() {
-            let line = line_result?;
-            if line.is_empty() {
+        let mut lines = NumberedLines::new(Self::DAY, reader);
+        for line in lines.by_ref() {
+            let line = line?;
+            if line.text.is_empty() {
                 elves.push(ElfData::new(mem::take(&mut current_elf)));
             } else {
-                current_elf.push(line.parse::<u32>().unwrap());
+                current_elf.push(
+                    line.text
+                        .parse::<u32>()
+                        .map_err(|_| line.error("a calorie count or a blank line"))?,
+                );
             }
         }
         if !current_elf.is_empty() {
             elves.push(ElfData::new(current_elf));
         }
+        // part 2 wants the top three
+        if elves.len() < 3 {
+            return Err(lines.end_of_input("at least three elves").into());
+        }
 
         elves.sort_by_key(|elf| std::cmp::Reverse(elf.total_calories));
-        elves
-    };
+        Ok(elves)
+    }
 
-    Ok((
-        elves[0].total_calories,
-        elves[0..3]
-            .iter()
-            .map(|elf| elf.total_calories)
-            .sum::<u32>(),
-    ))
-}
+    fn part1(elves: &Self::Input, _params: &()) -> u64 {
+        elves[0].total_calories
+    }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day1")?;
+    fn part2(elves: &Self::Input, _params: &()) -> u64 {
+        elves[0..3].iter().map(|elf| elf.total_calories).sum()
+    }
+}
 
-    println!("{}", part1);
-    println!("{}", part2);
+/// `size` elves, each carrying a handful of snacks.
+impl Generate for Day1 {
+    const SIZE: usize = 250;
 
-    Ok(())
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let mut lines = Vec::new();
+        // part 2 wants the top three
+        for elf in 0..size.max(3) {
+            if elf > 0 {
+                lines.push(String::new());
+            }
+            for _ in 0..rng.gen_range(1..=15) {
+                lines.push(rng.gen_range(1000..=60000).to_string());
+            }
+        }
+        join_lines(lines)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day1;
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day1").unwrap();
+        let (part1, part2) = solve_file::<Day1>("inputs-sample/day1").unwrap();
 
         assert_eq!(part1, 24000);
         assert_eq!(part2, 45000);
     }
+
+    #[test]
+    fn malformed() {
+        for (input, line) in [
+            ("", 1),
+            ("1000\n\n2000\n", 4),
+            ("1000\n\n2000\n\n3000\nlots\n", 6),
+        ] {
+            match solve::<Day1, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line, "{:?}", input),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn large_totals() {
+        let input = "4294967295\n4294967295\n\n4294967295\n\n1\n";
+        assert_eq!(
+            solve::<Day1, _>(input.as_bytes()).unwrap(),
+            (8_589_934_590, 12_884_901_886)
+        );
+    }
 }
@@ -1,55 +1,119 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+    visualize::{Animator, Frame, Visualize},
+};
 use console_bitmap::{draw_from_vec, BraillePatterns};
 use lazy_static::lazy_static;
+use rand::Rng;
 use regex::Regex;
-use std::io::BufRead;
+use std::{fmt, fmt::Write as _, io, io::BufRead};
 
 lazy_static! {
     static ref NOOP_INSTRUCTION: Regex = Regex::new(r"^noop$").unwrap();
-    static ref ADDX_INSTRUCTION: Regex = Regex::new(r"addx (-?\d+)$").unwrap();
+    static ref ADDX_INSTRUCTION: Regex = Regex::new(r"^addx (-?\d+)$").unwrap();
 }
 
-enum Instruction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
     Noop,
     AddX(i32),
 }
 
 impl Instruction {
-    #[allow(clippy::manual_map)]
-    fn parse(line: &str) -> Option<Self> {
+    /// How many cycles the instruction takes.
+    #[must_use]
+    pub fn cycles(&self) -> usize {
+        match self {
+            Instruction::Noop => 1,
+            Instruction::AddX(_) => 2,
+        }
+    }
+
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
         if NOOP_INSTRUCTION.is_match(line) {
             Some(Instruction::Noop)
         } else if let Some(v) = ADDX_INSTRUCTION.captures(line) {
-            Some(Instruction::AddX(v[1].parse::<i32>().unwrap()))
+            v[1].parse::<i32>().ok().map(Instruction::AddX)
         } else {
             None
         }
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-struct RegisterFile {
-    x: i32,
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::AddX(val) => write!(f, "addx {}", val),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RegisterFile {
+    pub x: i32,
 }
 
-struct ExecutionContext<'a> {
+/// Runs a program one cycle at a time, yielding the registers as they were during each cycle.
+///
+/// Once the program has finished the final register state is yielded forever.
+pub struct ExecutionContext<'a> {
     register: RegisterFile,
     program: &'a [Instruction],
     pc: usize,
-    cycle: usize,
+    /// Cycles already spent on the instruction at `pc`.
+    stage: usize,
+    /// Cycles run so far.
+    elapsed: usize,
 }
 
 impl<'a> ExecutionContext<'a> {
-    fn new(program: &'a [Instruction]) -> Self {
+    #[must_use]
+    pub fn new(program: &'a [Instruction]) -> Self {
         Self {
             register: RegisterFile { x: 1 },
             program,
             pc: 0,
-            cycle: 0,
+            stage: 0,
+            elapsed: 0,
         }
     }
+
+    /// The registers during the next cycle.
+    #[must_use]
+    pub fn register(&self) -> RegisterFile {
+        self.register
+    }
+
+    /// Index of the instruction being executed.
+    #[must_use]
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// The 1-based number of the next cycle, as the puzzle counts them.
+    #[must_use]
+    pub fn cycle(&self) -> usize {
+        self.elapsed + 1
+    }
+
+    /// The instruction being executed and how many of its cycles have already run, or `None` once
+    /// the program has finished.
+    #[must_use]
+    pub fn instruction(&self) -> Option<(&'a Instruction, usize)> {
+        self.program
+            .get(self.pc)
+            .map(|instruction| (instruction, self.stage))
+    }
+
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.pc >= self.program.len()
+    }
 }
 
 impl<'a> Iterator for ExecutionContext<'a> {
@@ -57,21 +121,22 @@ impl<'a> Iterator for ExecutionContext<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let register = self.register;
+        self.elapsed += 1;
         if self.pc >= self.program.len() {
             Some(register)
         } else {
             match &self.program[self.pc] {
                 Instruction::Noop => {
-                    assert!(self.cycle == 0);
+                    assert!(self.stage == 0);
                     self.pc += 1;
                 }
                 Instruction::AddX(val) => {
-                    if self.cycle == 1 {
+                    if self.stage == 1 {
                         self.register.x += val;
-                        self.cycle = 0;
+                        self.stage = 0;
                         self.pc += 1;
                     } else {
-                        self.cycle += 1;
+                        self.stage += 1;
                     }
                 }
             }
@@ -80,19 +145,64 @@ impl<'a> Iterator for ExecutionContext<'a> {
     }
 }
 
-fn parse_program(filename: &str) -> aoc::Result<Vec<Instruction>> {
-    let reader = aoc::file(filename)?;
+/// The lit pixels of the CRT, one row per scanline.
+pub struct Screen(Vec<Vec<bool>>);
 
-    Ok(reader
-        .lines()
-        .map(|line| Instruction::parse(&line.unwrap()).unwrap())
-        .collect())
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = draw_from_vec::<BraillePatterns>(&self.0);
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line)?;
+        }
+        Ok(())
+    }
 }
 
-fn solve(filename: &str) -> aoc::Result<(i32, Vec<Vec<bool>>)> {
-    let program = parse_program(filename)?;
+/// How far X may stray from zero, small enough that part 1's signal strengths add up within an
+/// `i32`.
+pub const X_LIMIT: i32 = i32::MAX / 720;
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u32 = 10;
+
+    type Input = Vec<Instruction>;
+    type Params = ();
+    type Answer1 = i32;
+    type Answer2 = Screen;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        // X as the program runs, which has to stay within X_LIMIT
+        let mut x = 1i32;
+        NumberedLines::new(Self::DAY, reader)
+            .map(|line| {
+                let line = line?;
+                let instruction = Instruction::parse(&line.text)
+                    .ok_or_else(|| line.error("`noop` or `addx <n>`"))?;
+                if let Instruction::AddX(val) = instruction {
+                    x = x
+                        .checked_add(val)
+                        .filter(|x| x.abs() <= X_LIMIT)
+                        .ok_or_else(|| {
+                            line.error(format!("`addx <n>` keeping X within ±{}", X_LIMIT))
+                        })?;
+                }
+                Ok(instruction)
+            })
+            .collect()
+    }
+
+    fn part1(program: &Self::Input, _params: &()) -> i32 {
+        part1(program)
+    }
 
-    Ok((part1(&program), part2(&program)))
+    fn part2(program: &Self::Input, _params: &()) -> Screen {
+        Screen(part2(program))
+    }
 }
 
 #[allow(clippy::similar_names)]
@@ -109,6 +219,11 @@ fn part1(program: &[Instruction]) -> i32 {
     at_20 + at_60 + at_100 + at_140 + at_180 + at_220
 }
 
+/// Whether the three pixel wide sprite centred on `x` covers `column`.
+fn sprite_covers(x: i32, column: usize) -> bool {
+    i32::try_from(column).is_ok_and(|column| column >= x - 1 && column <= x + 1)
+}
+
 fn part2(program: &[Instruction]) -> Vec<Vec<bool>> {
     let mut execution = ExecutionContext::new(program);
     let mut display = Vec::with_capacity(6);
@@ -116,32 +231,87 @@ fn part2(program: &[Instruction]) -> Vec<Vec<bool>> {
         let mut line = Vec::with_capacity(40);
         for j in 0..40 {
             let position = execution.next().unwrap().x;
-            line.push(j >= position - 1 && j <= position + 1);
+            line.push(sprite_covers(position, j));
         }
         display.push(line);
     }
     display
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day10")?;
+/// The pixels drawn up to and including `cycle`, with the sprite's position underneath.
+fn render_crt(display: &[Vec<bool>], cycle: usize, x: i32) -> String {
+    let mut out = String::new();
+    for (i, line) in display.iter().enumerate() {
+        for (j, lit) in line.iter().enumerate() {
+            out.push(match (i * 40 + j < cycle, lit) {
+                (false, _) => ' ',
+                (true, true) => '#',
+                (true, false) => '.',
+            });
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+    out.extend((0..40).map(|j| if sprite_covers(x, j) { '#' } else { '.' }));
+    write!(out, "\ncycle {}: X={}\n", cycle, x).expect("writing to a String can't fail");
+    out
+}
 
-    println!("{}", part1);
+/// Draws the CRT one cycle at a time, following the sprite.
+impl Visualize for Day10 {
+    fn visualize(
+        program: &Self::Input,
+        _params: &(),
+        animator: &mut Animator<'_>,
+    ) -> io::Result<()> {
+        let mut execution = ExecutionContext::new(program);
+        let mut display = vec![vec![false; 40]; 6];
+        for cycle in 0..240 {
+            let x = execution.next().unwrap().x;
+            display[cycle / 40][cycle % 40] = sprite_covers(x, cycle % 40);
+            animator.frame(|| Frame::Text(render_crt(&display, cycle + 1, x)))?;
+        }
 
-    for line in draw_from_vec::<BraillePatterns>(&part2) {
-        println!("{}", line);
+        animator.finish(&Frame::Pixels(display))
     }
+}
 
-    Ok(())
+/// At least `size` instructions, and enough to cover the 240 cycles the screen is drawn over.
+///
+/// X stays within the screen, give or take the sprite's width, as it does in real programs.
+impl Generate for Day10 {
+    const SIZE: usize = 140;
+
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let mut lines = Vec::new();
+        let (mut x, mut cycles) = (1, 0);
+        while lines.len() < size || cycles < 240 {
+            if rng.gen_ratio(1, 3) {
+                lines.push("noop".to_string());
+                cycles += 1;
+            } else {
+                let next = rng.gen_range(-1..=40);
+                lines.push(format!("addx {}", next - x));
+                x = next;
+                cycles += 2;
+            }
+        }
+        join_lines(lines)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{parse_program, solve, ExecutionContext};
+    use super::{Day10, ExecutionContext, X_LIMIT};
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file, Solution},
+    };
+    use adventlib::aoc;
 
     #[test]
     fn sample1() {
-        let program = parse_program("inputs-sample/day10-sample1").unwrap();
+        let program = Day10::parse(aoc::file("inputs-sample/day10-sample1").unwrap()).unwrap();
         let mut execution = ExecutionContext::new(&program);
 
         assert_eq!(execution.next().unwrap().x, 1);
@@ -166,8 +336,9 @@ mod test {
 
     #[test]
     fn sample2() {
-        let (part1, part2) = solve("inputs-sample/day10-sample2").unwrap();
+        let (part1, part2) = solve_file::<Day10>("inputs-sample/day10-sample2").unwrap();
         assert_eq!(part1, 13140);
+        let part2 = part2.0;
 
         assert_eq!(
             convert_display(&part2[0]),
@@ -194,4 +365,32 @@ mod test {
             "#######.......#######.......#######....."
         );
     }
+
+    #[test]
+    fn malformed() {
+        match solve::<Day10, _>("noop\naddx 3\naddx\n".as_bytes()) {
+            Err(Error::Parse(e)) => {
+                assert_eq!((e.day, e.line), (10, 3));
+                assert_eq!(e.expected, "`noop` or `addx <n>`");
+            }
+            Err(other) => panic!("expected a parse error, got {:?}", other),
+            Ok(_) => panic!("expected a parse error"),
+        }
+
+        for (input, line) in [
+            ("addx 2147483647\n", 1),
+            ("addx 2000000\nnoop\naddx 2000000\n", 3),
+            ("addx -2000000\naddx -2000000\n", 2),
+        ] {
+            match solve::<Day10, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line, "{:?}", input),
+                Err(other) => panic!("expected a parse error, got {:?}", other),
+                Ok(_) => panic!("expected a parse error"),
+            }
+        }
+        // as far as it can go, all the way through part 1
+        let input = format!("addx {}\n", X_LIMIT - 1);
+        let (part1, _) = solve::<Day10, _>(input.as_bytes()).unwrap();
+        assert_eq!(part1, X_LIMIT * 720);
+    }
 }
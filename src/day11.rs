@@ -1,7 +1,12 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    cycle::find_cycle,
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::{Line, NumberedLines},
+    solution::Solution,
+};
 use lazy_static::lazy_static;
+use rand::{seq::SliceRandom, Rng};
 use regex::Regex;
 use std::{cell::RefCell, io::BufRead, mem, usize};
 
@@ -19,6 +24,9 @@ lazy_static! {
 
 type IntType = u64;
 
+/// Rounds played in part 1.
+const PART1_ROUNDS: usize = 20;
+
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 enum Operation {
     Add(IntType),
@@ -28,9 +36,9 @@ enum Operation {
 impl Operation {
     fn parse(op: &str) -> Option<Operation> {
         if let Some(cap) = OPERATION_ADD.captures(op) {
-            Some(Operation::Add(cap[1].parse().unwrap()))
+            cap[1].parse().ok().map(Operation::Add)
         } else if let Some(cap) = OPERATION_MUL.captures(op) {
-            Some(Operation::Multiply(cap[1].parse().unwrap()))
+            cap[1].parse().ok().map(Operation::Multiply)
         } else if OPERATION_SQUARE.is_match(op) {
             Some(Operation::Square)
         } else {
@@ -38,15 +46,23 @@ impl Operation {
         }
     }
 
-    fn apply(self, old: IntType) -> IntType {
+    /// The new worry level, or `None` if it overflows.
+    fn apply(self, old: IntType) -> Option<IntType> {
         match self {
-            Operation::Add(v) => old.checked_add(v).unwrap(),
-            Operation::Multiply(v) => old.checked_mul(v).unwrap(),
-            Operation::Square => old.checked_mul(old).unwrap(),
+            Operation::Add(v) => old.checked_add(v),
+            Operation::Multiply(v) => old.checked_mul(v),
+            Operation::Square => old.checked_mul(old),
         }
     }
 }
 
+/// The lines of a monkey's notes that can only be checked once every monkey has been read.
+struct Notes {
+    operation: Line,
+    test: Line,
+    targets: [Line; 2],
+}
+
 #[derive(Debug, Clone)]
 struct Monkey {
     id: usize,
@@ -57,31 +73,97 @@ struct Monkey {
     false_monkey: usize,
 }
 impl Monkey {
-    fn parse<I: Iterator<Item = String>>(iter: &mut I) -> Option<Self> {
-        let Some(id) = MONKEY_HEADER.captures(&iter.next()?)
-            .map(|cap| cap[1].parse::<usize>().unwrap()) else { return None };
-        let Some(items) = STARTING_ITEMS.captures(&iter.next()?)
-            .map(|cap| cap[1].split(", ").map(|part| part.parse().unwrap()).collect::<Vec<_>>() ) else { return None };
-        let Some(operation) = OPERATION_LINE.captures(&iter.next()?)
-            .map(|cap| Operation::parse(&cap[1]).unwrap() ) else { return None };
-        let Some(test_divisible) = TEST_LINE.captures(&iter.next()?)
-            .map(|cap| cap[1].parse().unwrap() ) else { return None };
-        let Some(true_monkey) = TRUEFALSE_LINE.captures(&iter.next()?)
-            .map(|cap| cap[2].parse().unwrap() ) else { return None };
-        let Some(false_monkey) = TRUEFALSE_LINE.captures(&iter.next()?)
-            .map(|cap| cap[2].parse().unwrap() ) else { return None };
-
-        // maybe consume blank line;
-        mem::drop(iter.next());
-
-        Some(Monkey {
+    /// Parses the monkey numbered `id`, or returns `None` if the input has already run out after
+    /// at least two monkeys.
+    fn parse<R: BufRead>(lines: &mut NumberedLines<R>, id: usize) -> Result<Option<(Self, Notes)>> {
+        let header = format!("`Monkey {}:`", id);
+        let line = match lines.next() {
+            Some(line) => line?,
+            None if id > 1 => return Ok(None),
+            None => return Err(lines.end_of_input(header).into()),
+        };
+        match MONKEY_HEADER.captures(&line.text) {
+            Some(cap) if cap[1].parse() == Ok(id) => {}
+            _ => return Err(line.error(header).into()),
+        }
+
+        let (items, _) = Self::parse_line(
+            lines,
+            &STARTING_ITEMS,
+            "`  Starting items: <n>, ...`",
+            |cap| cap[1].split(", ").map(|part| part.parse().ok()).collect(),
+        )?;
+        let (operation, operation_line) = Self::parse_line(
+            lines,
+            &OPERATION_LINE,
+            "`  Operation: new = old <+|*> <n|old>`",
+            |cap| Operation::parse(&cap[1]),
+        )?;
+        let (test_divisible, test_line) = Self::parse_line(
+            lines,
+            &TEST_LINE,
+            "`  Test: divisible by <n>` with n above 0",
+            |cap| cap[1].parse().ok().filter(|&n| n > 0),
+        )?;
+        let (true_monkey, true_line) =
+            Self::parse_target(lines, id, "`    If true: throw to monkey <n>`")?;
+        let (false_monkey, false_line) =
+            Self::parse_target(lines, id, "`    If false: throw to monkey <n>`")?;
+
+        // monkeys are separated by a blank line
+        if let Some(line) = lines.next() {
+            let line = line?;
+            if !line.text.is_empty() {
+                return Err(line.error("a blank line between monkeys").into());
+            }
+        }
+
+        let monkey = Monkey {
             id,
             items: RefCell::new(items),
             operation,
             test_divisible,
             true_monkey,
             false_monkey,
-        })
+        };
+        let notes = Notes {
+            operation: operation_line,
+            test: test_line,
+            targets: [true_line, false_line],
+        };
+        Ok(Some((monkey, notes)))
+    }
+
+    /// Reads which monkey an item is thrown to, which can't be the thrower.
+    fn parse_target<R: BufRead>(
+        lines: &mut NumberedLines<R>,
+        id: usize,
+        expected: &str,
+    ) -> Result<(usize, Line)> {
+        let line = lines.expect_next(expected)?;
+        TRUEFALSE_LINE
+            .captures(&line.text)
+            .and_then(|cap| cap[2].parse().ok())
+            .filter(|&target| target != id)
+            .ok_or_else(|| {
+                line.error(format!("{} for a monkey other than {}", expected, id))
+                    .into()
+            })
+            .map(|target| (target, line))
+    }
+
+    /// Reads the next line, which has to match `regex` and convert with `convert`.
+    fn parse_line<R: BufRead, T>(
+        lines: &mut NumberedLines<R>,
+        regex: &Regex,
+        expected: &str,
+        convert: impl FnOnce(regex::Captures) -> Option<T>,
+    ) -> Result<(T, Line)> {
+        let line = lines.expect_next(expected)?;
+        match regex.captures(&line.text).and_then(convert) {
+            Some(value) => Ok((value, line)),
+            None => Err(line.error(expected).into()),
+        }
     }
 
     fn take_items(&self) -> Vec<IntType> {
@@ -99,44 +181,101 @@ impl Monkey {
     }
 }
 
+fn gcd(mut a: IntType, mut b: IntType) -> IntType {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
 #[derive(Clone)]
-struct GameState {
+pub struct GameState {
     monkies: Vec<Monkey>,
     items_considered: Vec<u64>,
     common_multiple: IntType,
 }
 
 impl GameState {
-    fn parse<I: Iterator<Item = String>>(iter: &mut I) -> Self {
+    fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut lines = NumberedLines::new(Day11::DAY, reader);
         let mut monkies = Vec::new();
-        while let Some(monkey) = Monkey::parse(iter) {
-            assert_eq!(monkey.id, monkies.len());
+        let mut notes = Vec::new();
+        while let Some((monkey, monkey_notes)) = Monkey::parse(&mut lines, monkies.len())? {
             monkies.push(monkey);
+            notes.push(monkey_notes);
         }
 
-        // FIXME: common_multiple should be lcm
-        Self {
+        let count = monkies.len();
+        let mut common_multiple: IntType = 1;
+        for (monkey, notes) in monkies.iter().zip(&notes) {
+            let targets = [monkey.true_monkey, monkey.false_monkey];
+            if let Some((_, line)) = targets
+                .iter()
+                .zip(&notes.targets)
+                .find(|&(&target, _)| target >= count)
+            {
+                return Err(line
+                    .error(format!("a monkey numbered below {}", count))
+                    .into());
+            }
+
+            let divisor = monkey.test_divisible;
+            common_multiple = (common_multiple / gcd(common_multiple, divisor))
+                .checked_mul(divisor)
+                .ok_or_else(|| {
+                    notes
+                        .test
+                        .error("a divisor that keeps every test's common multiple within 64 bits")
+                })?;
+        }
+        // part 2 keeps worry levels below the common multiple, which every operation has to cope
+        // with
+        for (monkey, notes) in monkies.iter().zip(&notes) {
+            if monkey.operation.apply(common_multiple - 1).is_none() {
+                return Err(notes
+                    .operation
+                    .error(format!(
+                        "an operation on worry levels below {} that fits in 64 bits",
+                        common_multiple
+                    ))
+                    .into());
+            }
+        }
+
+        let state = Self {
             items_considered: vec![0; monkies.len()],
-            common_multiple: monkies.iter().map(|v| v.test_divisible).product(),
+            common_multiple,
             monkies,
+        };
+        // nothing but dividing by three keeps part 1's worry levels down, so it's played out here
+        let mut part1 = state.clone();
+        if !(0..PART1_ROUNDS).all(|_| part1.try_run_round(true).is_some()) {
+            return Err(lines
+                .end_of_input(format!(
+                    "monkeys whose worry levels fit in 64 bits for {} rounds",
+                    PART1_ROUNDS
+                ))
+                .into());
         }
+        Ok(state)
     }
 
-    fn from_file(filename: &str) -> aoc::Result<Self> {
-        let reader = aoc::file(filename)?;
-        let mut lines = reader.lines().map(Result::unwrap);
-
-        Ok(GameState::parse(&mut lines))
+    fn run_round(&mut self, decrese_worry: bool) {
+        self.try_run_round(decrese_worry)
+            .expect("checked when parsing");
     }
 
-    fn run_round(&mut self, decrese_worry: bool) {
+    /// Plays a round, giving up part way through if a worry level overflows.
+    fn try_run_round(&mut self, decrese_worry: bool) -> Option<()> {
         for monkey in &self.monkies {
             for mut item in monkey.take_items() {
                 self.items_considered[monkey.id] += 1;
                 if decrese_worry {
-                    item = monkey.operation.apply(item) / 3;
+                    item = monkey.operation.apply(item)? / 3;
                 } else {
-                    item = monkey.operation.apply(item) % self.common_multiple;
+                    // starting items can be anything, so they're brought down to size first
+                    item =
+                        monkey.operation.apply(item % self.common_multiple)? % self.common_multiple;
                 }
                 if item % monkey.test_divisible == 0 {
                     self.monkies[monkey.true_monkey].thrown_item(item);
@@ -145,51 +284,195 @@ impl GameState {
                 }
             }
         }
+        Some(())
+    }
+
+    fn monkey_business(&self) -> u64 {
+        Self::monkey_business_of(self.items_considered.clone())
+    }
+
+    fn monkey_business_of(mut items_considered: Vec<u64>) -> u64 {
+        items_considered.sort_by_key(|k| std::cmp::Reverse(*k));
+        items_considered[0] * items_considered[1]
+    }
+
+    /// Where every item currently is, which is all that decides how later rounds play out.
+    fn item_positions(&self) -> Vec<Vec<IntType>> {
+        self.monkies
+            .iter()
+            .map(|monkey| monkey.items.borrow().clone())
+            .collect()
     }
 }
-fn solve(filename: &str) -> aoc::Result<(u64, u64)> {
-    let state = GameState::from_file(filename)?;
 
-    let part1 = {
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u32 = 11;
+
+    type Input = GameState;
+    type Params = ();
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        GameState::from_reader(reader)
+    }
+
+    fn part1(state: &Self::Input, _params: &()) -> u64 {
         let mut run_state = state.clone();
-        for _ in 0..20 {
+        for _ in 0..PART1_ROUNDS {
             run_state.run_round(true);
         }
-        let mut items_considered = run_state.items_considered.clone();
-        items_considered.sort_by_key(|k| std::cmp::Reverse(*k));
-        items_considered[0] * items_considered[1]
-    };
+        run_state.monkey_business()
+    }
 
-    let part2 = {
-        let mut run_state = state;
-        for _ in 0..10000 {
-            run_state.run_round(false);
-        }
-        let mut items_considered = run_state.items_considered.clone();
-        println!("{:?}", items_considered);
-        items_considered.sort_by_key(|k| std::cmp::Reverse(*k));
-        items_considered[0] * items_considered[1]
-    };
+    fn part2(state: &Self::Input, _params: &()) -> u64 {
+        let rounds = 10000;
+        let mut run_state = state.clone();
+
+        // worry levels are kept modulo the tests, so the items may well end up going round in
+        // circles; if they don't this is just the plain simulation
+        let cycle = find_cycle(
+            &mut run_state,
+            rounds,
+            |state| state.run_round(false),
+            GameState::item_positions,
+            |state| state.items_considered.clone(),
+        );
 
-    Ok((part1, part2))
+        match cycle {
+            Some(cycle) => GameState::monkey_business_of(
+                (0..state.monkies.len())
+                    .map(|i| cycle.extrapolate(rounds, |considered| considered[i]))
+                    .collect(),
+            ),
+            None => run_state.monkey_business(),
+        }
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day11")?;
+/// `size` monkeys, each throwing to two others, with one squaring worry levels as in the real
+/// puzzle.
+///
+/// Nothing keeps worry levels in check in part 1 but dividing by three, so monkeys are drawn
+/// again until the parser, which plays part 1 out, finds that its rounds fit in 64 bits.
+impl Generate for Day11 {
+    const SIZE: usize = 8;
 
-    println!("{}", part1);
-    println!("{}", part2);
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        const PRIMES: [IntType; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+        let count = size.max(3);
+        loop {
+            // different primes while they last, like the real puzzle
+            let mut divisors = PRIMES;
+            divisors.shuffle(rng);
+            let square = rng.gen_range(0..count);
 
-    Ok(())
+            let mut lines = Vec::new();
+            for id in 0..count {
+                if id > 0 {
+                    lines.push(String::new());
+                }
+                let items: Vec<String> = (0..rng.gen_range(1..=6))
+                    .map(|_| rng.gen_range(50..100).to_string())
+                    .collect();
+                let operation = if id == square {
+                    "old * old".to_string()
+                } else if rng.gen() {
+                    format!("old * {}", rng.gen_range(2..=19))
+                } else {
+                    format!("old + {}", rng.gen_range(1..=8))
+                };
+                let divisor = divisors
+                    .get(id)
+                    .copied()
+                    .unwrap_or_else(|| PRIMES[rng.gen_range(0..PRIMES.len())]);
+                let others: Vec<usize> = (0..count).filter(|&other| other != id).collect();
+                let targets: Vec<&usize> = others.choose_multiple(rng, 2).collect();
+
+                lines.push(format!("Monkey {}:", id));
+                lines.push(format!("  Starting items: {}", items.join(", ")));
+                lines.push(format!("  Operation: new = {}", operation));
+                lines.push(format!("  Test: divisible by {}", divisor));
+                lines.push(format!("    If true: throw to monkey {}", targets[0]));
+                lines.push(format!("    If false: throw to monkey {}", targets[1]));
+            }
+
+            let input = join_lines(lines);
+            if GameState::from_reader(input.as_bytes()).is_ok() {
+                return input;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{solve, GameState};
+    use super::{Day11, GameState};
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
+    use adventlib::aoc;
+
+    const MONKEY_0: &str = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+";
+    const MONKEY_1: &str = "
+Monkey 1:
+  Starting items: 54
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+";
+
+    #[test]
+    fn malformed() {
+        let valid = format!("{}{}", MONKEY_0, MONKEY_1);
+        solve::<Day11, _>(valid.as_bytes()).unwrap();
+
+        for (input, line) in [
+            (valid.replace("throw to monkey 1", "throw to monkey 2"), 5),
+            (
+                valid.replace("If false: throw to monkey 0", "If false: throw to monkey 1"),
+                13,
+            ),
+            (valid.replace("divisible by 19", "divisible by 0"), 11),
+            // the two divisors' common multiple is over 2^64
+            (
+                valid
+                    .replace("divisible by 23", "divisible by 4294967311")
+                    .replace("divisible by 19", "divisible by 4294967357"),
+                11,
+            ),
+            // squaring anything near the common multiple overflows
+            (
+                valid
+                    .replace("divisible by 23", "divisible by 4294967311")
+                    .replace("old + 6", "old * old"),
+                10,
+            ),
+            // part 1's worry levels overflow in a few rounds
+            (valid.replace("old + 6", "old * old"), 14),
+            (MONKEY_0.to_string(), 7),
+            (String::new(), 1),
+        ] {
+            match solve::<Day11, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line, "{}", input),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day11").unwrap();
+        let (part1, part2) = solve_file::<Day11>("inputs-sample/day11").unwrap();
 
         assert_eq!(part1, 10605);
         assert_eq!(part2, 2713310158);
@@ -197,7 +480,7 @@ mod test {
 
     #[test]
     fn round_tests() {
-        let mut state = GameState::from_file("inputs-sample/day11").unwrap();
+        let mut state = GameState::from_reader(aoc::file("inputs-sample/day11").unwrap()).unwrap();
 
         state.run_round(true);
 
@@ -206,14 +489,14 @@ mod test {
             state.monkies[1].test_items(),
             vec![2080, 25, 167, 207, 401, 1046]
         );
-        assert_eq!(state.monkies[2].test_items(), vec![]);
-        assert_eq!(state.monkies[3].test_items(), vec![]);
+        assert_eq!(state.monkies[2].test_items(), Vec::<u64>::new());
+        assert_eq!(state.monkies[3].test_items(), Vec::<u64>::new());
 
         state.run_round(true);
 
         assert_eq!(state.monkies[0].test_items(), vec![695, 10, 71, 135, 350]);
         assert_eq!(state.monkies[1].test_items(), vec![43, 49, 58, 55, 362]);
-        assert_eq!(state.monkies[2].test_items(), vec![]);
-        assert_eq!(state.monkies[3].test_items(), vec![]);
+        assert_eq!(state.monkies[2].test_items(), Vec::<u64>::new());
+        assert_eq!(state.monkies[3].test_items(), Vec::<u64>::new());
     }
 }
@@ -1,114 +1,205 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc::{self, valid_neigbors_no_diagonal, CodeTimer};
+use crate::{
+    error::Result,
+    export::{Export, Image, Rgb},
+    generate::{join_lines, Generate, Random},
+    grid::{Grid, Pos},
+    input::NumberedLines,
+    solution::Solution,
+};
 use pathfinding::directed::astar::astar;
+use rand::{seq::SliceRandom, Rng};
 use rayon::prelude::*;
 use std::io::BufRead;
 
-fn route(
-    start: &(usize, usize),
-    end: &(usize, usize),
-    map: &[Vec<u8>],
-    m: usize,
-    n: usize,
-) -> Option<(Vec<(usize, usize)>, usize)> {
+fn route(start: &Pos, end: &Pos, map: &Grid<u8>) -> Option<(Vec<Pos>, usize)> {
     astar(
         start,
-        |node| {
-            let cur = map[node.0][node.1];
-            valid_neigbors_no_diagonal(*node, m, n)
-                .into_iter()
-                .filter_map(|neigh| {
-                    let neigh_cell = map[neigh.0][neigh.1];
-                    if neigh_cell <= cur || (neigh_cell - cur) == 1 {
-                        Some((neigh, 1))
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<((usize, usize), usize)>>()
+        |&node| {
+            let cur = map[node];
+            map.neighbours4(node)
+                .filter(|&neigh| map[neigh] <= cur + 1)
+                .map(|neigh| (neigh, 1))
+                .collect::<Vec<(Pos, usize)>>()
         },
         |node| node.0.abs_diff(end.0) + node.1.abs_diff(end.1),
         |node| node == end,
     )
 }
 
-fn solve(filename: &str) -> aoc::Result<(usize, usize)> {
-    let reader = aoc::file(filename)?;
-
-    let mut timer = CodeTimer::new();
-
-    let mut map: Vec<Vec<u8>> = Vec::new();
-    let mut start: Option<(usize, usize)> = None;
-    let mut end: Option<(usize, usize)> = None;
-    for (i, line) in reader.lines().enumerate() {
-        let line = line?;
-        let mut row = Vec::with_capacity(line.len());
-        for (j, mut ch) in line.bytes().enumerate() {
-            if ch == b'S' {
-                assert!(start.is_none());
-                start = Some((i, j));
-                ch = b'a';
-            } else if ch == b'E' {
-                assert!(end.is_none());
-                end = Some((i, j));
-                ch = b'z';
+#[derive(Debug)]
+pub struct Heightmap {
+    map: Grid<u8>,
+    start: Pos,
+    end: Pos,
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u32 = 12;
+
+    type Input = Heightmap;
+    type Params = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let mut lines = NumberedLines::new(Self::DAY, reader);
+        let mut start: Option<Pos> = None;
+        let mut end: Option<Pos> = None;
+        let map = Grid::parse(
+            &mut lines,
+            "a row of heights a-z, with one S and one E",
+            |pos, ch| match ch {
+                'S' => start.replace(pos).is_none().then_some(b'a'),
+                'E' => end.replace(pos).is_none().then_some(b'z'),
+                'a'..='z' => Some(ch as u8),
+                _ => None,
+            },
+        )?;
+
+        match (start, end) {
+            // S is at height `a` too, so part 2 has a route whenever part 1 does
+            (Some(start), Some(end)) if route(&start, &end, &map).is_some() => {
+                Ok(Heightmap { map, start, end })
             }
-            row.push(ch);
+            (Some(_), Some(_)) => Err(lines
+                .end_of_input("a map where E can be reached from S")
+                .into()),
+            _ => Err(lines.end_of_input("a map with one S and one E").into()),
         }
-        map.push(row);
     }
 
-    timer.split("parse");
+    fn part1(heightmap: &Self::Input, _params: &()) -> usize {
+        route(&heightmap.start, &heightmap.end, &heightmap.map)
+            .expect("checked when parsing")
+            .1
+    }
 
-    let start = start.unwrap();
-    let end = end.unwrap();
-    let m = map.len();
-    let n = map[0].len();
+    fn part2(heightmap: &Self::Input, _params: &()) -> usize {
+        let Heightmap { map, end, .. } = heightmap;
 
-    let part1_route = route(&start, &end, &map, m, n).expect("route not found");
+        // Brute force yaaaay
+        map.positions()
+            .par_bridge()
+            .filter(|&pos| map[pos] == b'a')
+            .filter_map(|pos| route(&pos, end, map).map(|(_, len)| len))
+            .min()
+            .expect("checked when parsing")
+    }
+}
 
-    timer.split("part1");
+const ROUTE: Rgb = [220, 40, 40];
 
-    // Brute force yaaaay
-    let map_ref = &map; // we need to do this because Rust is being weird
-    let part2 = (0..m)
-        .into_par_iter()
-        .flat_map(|i| {
-            (0..m).into_par_iter().filter_map(move |j| {
-                if map_ref[i][j] == b'a' {
-                    route(&(i, j), &end, map_ref, m, n).map(|(_, len)| len)
-                } else {
-                    None
-                }
-            })
-        })
-        .min()
-        .expect("shortest route not found");
+/// Low ground dark and high ground light.
+fn terrain(height: u8) -> Rgb {
+    let shade = 40 + (height - b'a') * 8;
+    [shade / 2, shade, shade / 2]
+}
 
-    timer.stop("part2");
+/// The heightmap with part 1's route drawn over it, or walked one step at a time.
+impl Export for Day12 {
+    fn snapshot(heightmap: &Self::Input, _params: &()) -> Image {
+        let mut image = Image::from_grid(&heightmap.map, |&height| terrain(height));
+        if let Some((path, _)) = route(&heightmap.start, &heightmap.end, &heightmap.map) {
+            for pos in path {
+                image.set(pos, ROUTE);
+            }
+        }
+        image
+    }
 
-    Ok((part1_route.1, part2))
+    fn frames(
+        heightmap: &Self::Input,
+        _params: &(),
+        every: usize,
+        frame: &mut dyn FnMut(&Image) -> Result<()>,
+    ) -> Result<()> {
+        let mut image = Image::from_grid(&heightmap.map, |&height| terrain(height));
+        let path = route(&heightmap.start, &heightmap.end, &heightmap.map)
+            .map(|(path, _)| path)
+            .unwrap_or_default();
+        for (step, pos) in path.into_iter().enumerate() {
+            image.set(pos, ROUTE);
+            if step.is_multiple_of(every) {
+                frame(&image)?;
+            }
+        }
+
+        frame(&image)
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day12")?;
+/// A heightmap `size` rows tall and four times as wide, with S on the left edge and E somewhere on
+/// the right.
+///
+/// A staircase from S to E that only ever heads towards E climbs from `a` to `z` one step at a
+/// time, so there is always a route; everything off it is random.
+impl Generate for Day12 {
+    const SIZE: usize = 41;
+
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let height = size.max(1);
+        // far enough for the staircase to climb all 25 steps
+        let width = (height * 4).max(27);
+        let mut map = Grid::new(width, height, b'a');
+        for pos in map.positions() {
+            map[pos] = rng.gen_range(b'a'..=b'z');
+        }
 
-    println!("{}", part1);
-    println!("{}", part2);
+        let start = (rng.gen_range(0..height), 0);
+        let end = (
+            rng.gen_range(0..height),
+            rng.gen_range((width / 2).max(26)..width),
+        );
+        // every step goes right, or up or down towards E, in any order
+        let mut steps = vec![false; end.1];
+        steps.resize(end.1 + start.0.abs_diff(end.0), true);
+        steps.shuffle(rng);
+
+        let mut pos = start;
+        for (i, &vertical) in steps.iter().enumerate() {
+            let climbed = i * 25 / steps.len();
+            map[pos] = b'a' + u8::try_from(climbed).expect("at most 24");
+            pos = match (vertical, end.0 < start.0) {
+                (false, _) => (pos.0, pos.1 + 1),
+                (true, true) => (pos.0 - 1, pos.1),
+                (true, false) => (pos.0 + 1, pos.1),
+            };
+        }
+        map[start] = b'S';
+        map[end] = b'E';
 
-    Ok(())
+        join_lines(
+            map.rows()
+                .map(|row| row.iter().map(|&height| char::from(height)).collect()),
+        )
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day12;
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day12").unwrap();
+        let (part1, part2) = solve_file::<Day12>("inputs-sample/day12").unwrap();
 
         assert_eq!(part1, 31);
         assert_eq!(part2, 29);
     }
+
+    #[test]
+    fn malformed() {
+        for (input, line) in [("SzE\n", 2), ("Sab\n", 2), ("SE\nS?\n", 2)] {
+            match solve::<Day12, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line, "{:?}", input),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
 }
@@ -1,12 +1,16 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc::{self};
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+};
+use rand::Rng;
 use serde::Deserialize;
 use std::{cmp::Ordering, io::BufRead};
 
 #[derive(Deserialize, Eq, PartialEq)]
 #[serde(untagged)]
-enum Value<'a> {
+pub enum Value<'a> {
     Integer(u32),
     List(Vec<Value<'a>>),
 
@@ -82,58 +86,107 @@ fn correct_order(left: &Value, right: &Value) -> bool {
     matches!(left.cmp(right), Ordering::Less | Ordering::Equal)
 }
 
-fn solve(filename: &str) -> aoc::Result<(usize, usize)> {
-    let reader = aoc::file(filename)?;
-    let mut lines = reader.lines();
+pub struct Day13;
 
-    let mut packets = Vec::new();
-    let mut i = 1;
-    let mut part1 = 0;
-    while let Some(Ok(left_str)) = lines.next() {
-        let right_str = lines.next().unwrap()?;
+impl Solution for Day13 {
+    const DAY: u32 = 13;
 
-        let left: Value = serde_json::from_str(&left_str).unwrap();
-        let right: Value = serde_json::from_str(&right_str).unwrap();
+    type Input = Vec<(Value<'static>, Value<'static>)>;
+    type Params = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let expected = "a packet such as `[1,[2,3]]`";
+        let mut lines = NumberedLines::new(Self::DAY, reader);
+        let packet = |text: &str| serde_json::from_str::<Value>(text).ok();
+
+        let mut pairs = Vec::new();
+        while let Some(left_line) = lines.next() {
+            let left_line = left_line?;
+            let left = packet(&left_line.text).ok_or_else(|| left_line.error(expected))?;
+            let right_line = lines.expect_next(expected)?;
+            let right = packet(&right_line.text).ok_or_else(|| right_line.error(expected))?;
+
+            if let Some(blank) = lines.next() {
+                let blank = blank?;
+                if !blank.text.is_empty() {
+                    return Err(blank.error("a blank line between pairs").into());
+                }
+            }
 
-        let _blank = lines.next();
-        if correct_order(&left, &right) {
-            part1 += i;
+            pairs.push((left, right));
         }
 
-        packets.push(left);
-        packets.push(right);
+        Ok(pairs)
+    }
 
-        i += 1;
+    fn part1(pairs: &Self::Input, _params: &()) -> usize {
+        pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, (left, right))| correct_order(left, right))
+            .map(|(idx, _)| idx + 1)
+            .sum()
     }
 
-    packets.push(Value::Divider(2));
-    packets.push(Value::Divider(6));
+    fn part2(pairs: &Self::Input, _params: &()) -> usize {
+        let dividers = [Value::Divider(2), Value::Divider(6)];
 
-    packets.sort();
+        let mut packets: Vec<&Value> = pairs
+            .iter()
+            .flat_map(|(left, right)| [left, right])
+            .chain(&dividers)
+            .collect();
 
-    let part2 = packets
-        .iter()
-        .enumerate()
-        .filter(|(_, el)| matches!(el, Value::Divider(_)))
-        .map(|(idx, _)| idx + 1)
-        .product();
+        packets.sort();
 
-    Ok((part1, part2))
+        packets
+            .iter()
+            .enumerate()
+            .filter(|(_, el)| matches!(el, Value::Divider(_)))
+            .map(|(idx, _)| idx + 1)
+            .product()
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day13")?;
+/// A packet nested at most `depth` lists deep, written out as it would appear in the input.
+fn random_packet(rng: &mut Random, depth: u32) -> String {
+    let items: Vec<String> = (0..rng.gen_range(0..=5))
+        .map(|_| {
+            if depth > 0 && rng.gen_ratio(1, 3) {
+                random_packet(rng, depth - 1)
+            } else {
+                rng.gen_range(0..=10).to_string()
+            }
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
 
-    println!("{}", part1);
-    println!("{}", part2);
+/// `size` pairs of packets, nested up to four lists deep.
+impl Generate for Day13 {
+    const SIZE: usize = 150;
 
-    Ok(())
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let mut lines = Vec::new();
+        for pair in 0..size.max(1) {
+            if pair > 0 {
+                lines.push(String::new());
+            }
+            lines.push(random_packet(rng, 4));
+            lines.push(random_packet(rng, 4));
+        }
+        join_lines(lines)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::correct_order;
-    use crate::{solve, Value};
+    use super::correct_order;
+    use super::{Day13, Value};
+    use crate::solution::solve_file;
+    use proptest::prelude::*;
     use std::cmp::Ordering;
 
     fn value(v: &str) -> Value {
@@ -156,7 +209,7 @@ mod test {
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day13").unwrap();
+        let (part1, part2) = solve_file::<Day13>("inputs-sample/day13").unwrap();
 
         assert_eq!(part1, 13);
         assert_eq!(part2, 140);
@@ -216,4 +269,40 @@ mod test {
         assert_eq!(v[2], &v3);
         assert_eq!(v[3], &v4);
     }
+
+    /// Packets built from small integers, so that lots of them compare equal at some depth.
+    fn packet() -> impl Strategy<Value = Value<'static>> {
+        let leaf = prop_oneof![
+            4 => (0..4u32).prop_map(Value::Integer),
+            1 => (0..4u32).prop_map(Value::Divider),
+        ];
+        leaf.prop_recursive(4, 32, 4, |inner| {
+            prop::collection::vec(inner, 0..4).prop_map(Value::List)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn total_order(a in packet(), b in packet(), c in packet()) {
+            prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+
+            if a <= b && b <= c {
+                prop_assert!(a <= c, "{:?} <= {:?} <= {:?}", a, b, c);
+            }
+            if a.cmp(&b) == Ordering::Equal {
+                prop_assert_eq!(a.cmp(&c), b.cmp(&c));
+            }
+        }
+
+        #[test]
+        fn sorted(mut packets in prop::collection::vec(packet(), 0..12)) {
+            packets.sort();
+            for (i, a) in packets.iter().enumerate() {
+                for b in &packets[i..] {
+                    prop_assert!(a <= b, "{:?} > {:?}", a, b);
+                }
+            }
+        }
+    }
 }
@@ -1,15 +1,37 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
-use std::{cmp, io::BufRead};
+use crate::{
+    error::Result,
+    export::{Export, Image, Rgb, BLACK},
+    generate::{join_lines, Generate, Random},
+    grid::Grid,
+    input::NumberedLines,
+    solution::Solution,
+    visualize::{Animator, Frame, Visualize},
+};
+use rand::Rng;
+use std::{cmp, io, io::BufRead};
+
+/// Column the sand pours in from.
+const SOURCE_X: usize = 500;
+/// Rock deeper than this is rejected, so the pile of sand on the floor can't spread past x = 0.
+const MAX_Y: usize = 400;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Tile {
+pub enum Tile {
     Empty,
     Wall,
     Sand,
     Floor,
 }
+impl Tile {
+    fn colour(self) -> Rgb {
+        match self {
+            Tile::Empty => BLACK,
+            Tile::Wall => [128, 128, 128],
+            Tile::Sand => [230, 200, 100],
+            Tile::Floor => [110, 80, 50],
+        }
+    }
+}
 impl std::fmt::Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -25,163 +47,305 @@ impl std::fmt::Display for Tile {
     }
 }
 
-#[allow(dead_code)]
-fn dump_map(map: &[Vec<Tile>]) {
-    for line in map {
-        print!("|");
-        for ch in line {
-            print!("{}", ch);
+#[derive(Debug, Clone)]
+pub struct Cave {
+    map: Grid<Tile>,
+    start_x: usize,
+    max_y: usize,
+}
+impl Cave {
+    /// Drops one grain of sand from the source, returning where it came to rest, or `None` if
+    /// the source is already blocked or `abyss` is set and it fell past the lowest rock.
+    fn drop_grain(&mut self, abyss: bool) -> Option<(usize, usize)> {
+        let map = &mut self.map;
+        let mut cur_x = self.start_x;
+        let mut cur_y = 0;
+        if map[(cur_y, cur_x)] != Tile::Empty {
+            return None;
+        }
+
+        loop {
+            assert!(cur_y < self.max_y);
+            assert_eq!(map[(cur_y, cur_x)], Tile::Empty);
+            if map[(cur_y + 1, cur_x)] == Tile::Empty {
+                cur_y += 1;
+            } else if map[(cur_y + 1, cur_x - 1)] == Tile::Empty {
+                cur_x -= 1;
+                cur_y += 1;
+            } else if map[(cur_y + 1, cur_x + 1)] == Tile::Empty {
+                cur_x += 1;
+                cur_y += 1;
+            } else {
+                map[(cur_y, cur_x)] = Tile::Sand;
+                return Some((cur_y, cur_x));
+            }
+            if abyss && (cur_y >= self.max_y || map[(cur_y + 1, cur_x)] == Tile::Floor) {
+                return None;
+            }
         }
-        println!("|");
     }
-}
 
-#[allow(clippy::needless_range_loop)]
-#[allow(clippy::too_many_lines)]
-fn solve(filename: &str) -> aoc::Result<(u32, u32)> {
-    let reader = aoc::file(filename)?;
-
-    let paths: Vec<Vec<(usize, usize)>> = reader
-        .lines()
-        .map(|line| {
-            line.unwrap()
-                .split(" -> ")
-                .map(|point| {
-                    let mut parts = point.split(',');
-                    (
-                        parts.next().unwrap().parse::<usize>().unwrap(),
-                        parts.next().unwrap().parse::<usize>().unwrap(),
-                    )
-                })
-                .collect()
-        })
-        .collect();
-
-    let mut min_x = usize::MAX;
-    let mut max_x = 0;
-    let mut max_y = 0;
-    for path in &paths {
-        for (x, y) in path {
-            min_x = cmp::min(min_x, *x);
-            max_x = cmp::max(max_x, *x);
-            max_y = cmp::max(max_y, *y);
+    /// The columns holding any of `tiles`, with one spare either side.
+    fn columns_holding(&self, tiles: &[Tile]) -> (usize, usize) {
+        let mut columns =
+            (0..self.map.width()).filter(|&x| self.map.column(x).any(|tile| tiles.contains(tile)));
+        let min = columns.next().unwrap_or(self.start_x);
+        let max = columns.next_back().unwrap_or(min);
+        (
+            min.saturating_sub(1),
+            cmp::min(max + 1, self.map.width() - 1),
+        )
+    }
+
+    /// Rock and sand above the floor, between the given columns.
+    fn pixels(&self, (min_x, max_x): (usize, usize)) -> Vec<Vec<bool>> {
+        self.map
+            .rows()
+            .take(self.max_y)
+            .map(|row| {
+                row[min_x..=max_x]
+                    .iter()
+                    .map(|tile| *tile != Tile::Empty)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The whole cave down to the floor, between the given columns.
+    fn image(&self, (min_x, max_x): (usize, usize)) -> Image {
+        let mut image = Image::new(max_x - min_x + 1, self.max_y + 1, BLACK);
+        for y in 0..=self.max_y {
+            for x in min_x..=max_x {
+                image.set((y, x - min_x), self.map[(y, x)].colour());
+            }
         }
+        image
     }
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u32 = 14;
 
-    // FIXME: this is gross
-    assert!(min_x > 300);
-    min_x -= 300;
-    max_x += 300;
-    max_y += 2;
-    assert!(min_x < max_x);
+    type Input = Cave;
+    type Params = ();
+    type Answer1 = u32;
+    type Answer2 = u32;
 
-    let mut map: Vec<Vec<Tile>> = vec![vec![Tile::Empty; max_x - min_x]; max_y + 1];
+    #[allow(clippy::needless_range_loop)]
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let expected = format!(
+            "a path of horizontal and vertical lines like `498,4 -> 498,6 -> 496,6`, \
+             with x below {} and y at most {}",
+            2 * SOURCE_X,
+            MAX_Y
+        );
+        let paths: Vec<Vec<(usize, usize)>> = NumberedLines::new(Self::DAY, reader)
+            .map(|line| {
+                let line = line?;
+                let path: Option<Vec<(usize, usize)>> = line
+                    .text
+                    .split(" -> ")
+                    .map(|point| {
+                        let (x, y) = point.split_once(',')?;
+                        Some((x.parse().ok()?, y.parse().ok()?))
+                            .filter(|&(x, y)| x < 2 * SOURCE_X && y <= MAX_Y)
+                    })
+                    .collect();
 
-    for path in paths {
-        let mut iter = path.into_iter();
-        let mut prev = iter.next().unwrap();
-        for cur in iter {
-            if prev.0 == cur.0 {
-                for j in cmp::min(prev.1, cur.1)..=cmp::max(prev.1, cur.1) {
-                    map[j][cur.0 - min_x] = Tile::Wall;
+                match path {
+                    Some(path)
+                        if path
+                            .windows(2)
+                            .all(|w| w[0].0 == w[1].0 || w[0].1 == w[1].1) =>
+                    {
+                        Ok(path)
+                    }
+                    _ => Err(line.error(&expected).into()),
                 }
-            } else if prev.1 == cur.1 {
-                for i in cmp::min(prev.0, cur.0)..=cmp::max(prev.0, cur.0) {
-                    map[cur.1][i - min_x] = Tile::Wall;
+            })
+            .collect::<Result<_>>()?;
+
+        let mut min_x = SOURCE_X;
+        let mut max_x = SOURCE_X;
+        let mut max_y = 0;
+        for path in &paths {
+            for (x, y) in path {
+                min_x = cmp::min(min_x, *x);
+                max_x = cmp::max(max_x, *x);
+                max_y = cmp::max(max_y, *y);
+            }
+        }
+
+        // sand on the floor piles up into a triangle below the source, so leave room for that
+        // and a spare column either side
+        max_y += 2;
+        min_x = cmp::min(min_x, SOURCE_X - max_y).saturating_sub(1);
+        max_x = cmp::max(max_x, SOURCE_X + max_y) + 1;
+
+        let mut map = Grid::new(max_x - min_x, max_y + 1, Tile::Empty);
+
+        for path in paths {
+            let mut iter = path.into_iter();
+            let mut prev = iter.next().unwrap();
+            for cur in iter {
+                if prev.0 == cur.0 {
+                    for j in cmp::min(prev.1, cur.1)..=cmp::max(prev.1, cur.1) {
+                        map[(j, cur.0 - min_x)] = Tile::Wall;
+                    }
+                } else {
+                    for i in cmp::min(prev.0, cur.0)..=cmp::max(prev.0, cur.0) {
+                        map[(cur.1, i - min_x)] = Tile::Wall;
+                    }
                 }
-            } else {
-                unimplemented!("diagonal line??")
+                prev = cur;
             }
-            prev = cur;
         }
+
+        for x in 0..(max_x - min_x) {
+            map[(max_y, x)] = Tile::Floor;
+        }
+
+        Ok(Cave {
+            map,
+            start_x: SOURCE_X - min_x,
+            max_y,
+        })
     }
 
-    for x in 0..(max_x - min_x) {
-        map[max_y][x] = Tile::Floor;
+    fn part1(cave: &Self::Input, _params: &()) -> u32 {
+        let mut cave = cave.clone();
+        let mut n_landed = 0;
+        while cave.drop_grain(true).is_some() {
+            n_landed += 1;
+        }
+
+        n_landed
     }
 
-    let mut n_landed = 0;
-    let mut landed = true;
-    let start_x = 500 - min_x;
-    while landed {
-        let mut cur_x = start_x;
-        let mut cur_y = 0;
-        landed = false;
+    fn part2(cave: &Self::Input, _params: &()) -> u32 {
+        let mut cave = cave.clone();
+        let mut n_landed = 0;
+        while cave.drop_grain(false).is_some() {
+            n_landed += 1;
+        }
 
-        loop {
-            assert!(cur_y < max_y);
-            assert_eq!(map[cur_y][cur_x], Tile::Empty);
-            if map[cur_y + 1][cur_x] == Tile::Empty {
-                cur_y += 1;
-            } else if map[cur_y + 1][cur_x - 1] == Tile::Empty {
-                cur_x -= 1;
-                cur_y += 1;
-            } else if map[cur_y + 1][cur_x + 1] == Tile::Empty {
-                cur_x += 1;
-                cur_y += 1;
-            } else {
-                map[cur_y][cur_x] = Tile::Sand;
-                n_landed += 1;
-                landed = true;
-                break;
-            }
-            if cur_y >= max_y || map[cur_y + 1][cur_x] == Tile::Floor {
-                break;
-            }
+        n_landed
+    }
+}
+
+/// Pours sand for part 1, one frame per grain that comes to rest.
+impl Visualize for Day14 {
+    fn visualize(cave: &Self::Input, _params: &(), animator: &mut Animator<'_>) -> io::Result<()> {
+        let mut cave = cave.clone();
+        let columns = cave.columns_holding(&[Tile::Wall]);
+        while cave.drop_grain(true).is_some() {
+            animator.frame(|| Frame::Pixels(cave.pixels(columns)))?;
         }
+
+        animator.finish(&Frame::Pixels(cave.pixels(columns)))
     }
+}
 
-    let part1 = n_landed;
+/// The pile left by part 2, or part 1's sand pouring in.
+impl Export for Day14 {
+    fn snapshot(cave: &Self::Input, _params: &()) -> Image {
+        let mut cave = cave.clone();
+        while cave.drop_grain(false).is_some() {}
 
-    while map[0][start_x] == Tile::Empty {
-        let mut cur_x = start_x;
-        let mut cur_y = 0;
+        cave.image(cave.columns_holding(&[Tile::Wall, Tile::Sand]))
+    }
 
-        loop {
-            assert!(cur_y < max_y);
-            assert_eq!(map[cur_y][cur_x], Tile::Empty);
-            if map[cur_y + 1][cur_x] == Tile::Empty {
-                cur_y += 1;
-            } else if map[cur_y + 1][cur_x - 1] == Tile::Empty {
-                cur_x -= 1;
-                cur_y += 1;
-            } else if map[cur_y + 1][cur_x + 1] == Tile::Empty {
-                cur_x += 1;
-                cur_y += 1;
-            } else {
-                map[cur_y][cur_x] = Tile::Sand;
-                n_landed += 1;
-                break;
-            }
-            if cur_y >= max_y {
-                break;
+    fn frames(
+        cave: &Self::Input,
+        _params: &(),
+        every: usize,
+        frame: &mut dyn FnMut(&Image) -> Result<()>,
+    ) -> Result<()> {
+        let mut cave = cave.clone();
+        let columns = cave.columns_holding(&[Tile::Wall]);
+        let mut n_landed: usize = 0;
+        while cave.drop_grain(true).is_some() {
+            n_landed += 1;
+            if n_landed.is_multiple_of(every) {
+                frame(&cave.image(columns))?;
             }
         }
-    }
 
-    let part2 = n_landed;
-
-    Ok((part1, part2))
+        frame(&cave.image(columns))
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day14")?;
+/// `size` paths of rock, each a few horizontal and vertical lines, scattered below the source.
+impl Generate for Day14 {
+    const SIZE: usize = 150;
 
-    println!("{}", part1);
-    println!("{}", part2);
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        join_lines((0..size.max(1)).map(|_| {
+            let mut point = (
+                rng.gen_range(SOURCE_X - 60..=SOURCE_X + 60),
+                rng.gen_range(2..=170),
+            );
+            let mut points = vec![point];
+            let mut horizontal = rng.gen();
+            for _ in 0..rng.gen_range(1..=5) {
+                let length = rng.gen_range(1..=8);
+                let (along, max) = if horizontal {
+                    (&mut point.0, 2 * SOURCE_X - 1)
+                } else {
+                    (&mut point.1, MAX_Y)
+                };
+                *along = if rng.gen() {
+                    along.saturating_sub(length).max(2)
+                } else {
+                    (*along + length).min(max)
+                };
+                points.push(point);
+                horizontal = !horizontal;
+            }
 
-    Ok(())
+            let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+            points.join(" -> ")
+        }))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day14;
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day14").unwrap();
+        let (part1, part2) = solve_file::<Day14>("inputs-sample/day14").unwrap();
 
         assert_eq!(part1, 24);
         assert_eq!(part2, 93);
     }
+
+    #[test]
+    fn malformed() {
+        for (input, line) in [
+            ("498,4 -> 498,6\n503,4 -> 502,5\n", 2),
+            ("498,4 -> 498\n", 1),
+            ("498,4 -> 498,6\n500,401 -> 501,401\n", 2),
+            ("1000,4 -> 1000,6\n", 1),
+            ("0,4 -> 0,6\n1000,4 -> 1000,6\n", 2),
+        ] {
+            match solve::<Day14, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn no_rock() {
+        // the sand just piles up on the floor, two rows down
+        assert_eq!(solve::<Day14, _>("".as_bytes()).unwrap(), (0, 4));
+    }
 }
@@ -1,10 +1,17 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc::{self, point2d::Point2D, CodeTimer};
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::{Line, NumberedLines},
+    intspan::IntSpan,
+    reference::Reference,
+    solution::Solution,
+};
+use adventlib::aoc::point2d::Point2D;
 use lazy_static::lazy_static;
+use rand::{seq::SliceRandom, Rng};
 use rayon::prelude::*;
 use regex::Regex;
-use std::{cmp, io::BufRead};
+use std::io::BufRead;
 
 lazy_static! {
     static ref PARSE_REGEX: Regex =
@@ -37,18 +44,20 @@ impl SensorExtents {
     }
 }
 #[derive(Debug)]
-struct Sensor {
+pub struct Sensor {
     //_sensor: Point2D,
-    //_beacon: Point2D,
+    beacon: Point2D,
     extents: SensorExtents,
 }
 impl Sensor {
-    fn parse(line: Result<String, std::io::Error>) -> aoc::Result<Self> {
+    fn parse(line: Result<Line>) -> Result<Self> {
         let line = line?;
-        if let Some(cap) = PARSE_REGEX.captures(&line) {
-            let sensor = Point2D(cap[1].parse()?, cap[2].parse()?);
-            let beacon = Point2D(cap[3].parse()?, cap[4].parse()?);
-
+        let points = PARSE_REGEX.captures(&line.text).and_then(|cap| {
+            // read as i32 so distances and extents comfortably fit in an i64
+            let coord = |i: usize| cap[i].parse::<i32>().ok().map(i64::from);
+            Some((Point2D(coord(1)?, coord(2)?), Point2D(coord(3)?, coord(4)?)))
+        });
+        if let Some((sensor, beacon)) = points {
             let distance = (sensor - beacon).manhattan_distance();
             let extents = SensorExtents {
                 min_x: sensor.0 - distance,
@@ -57,232 +66,281 @@ impl Sensor {
                 max_y: sensor.1 + distance,
                 center_y: sensor.1,
             };
-            Ok(Sensor {
-                /*_sensor: sensor, _beacon: beacon,*/ extents,
-            })
+            Ok(Sensor { beacon, extents })
         } else {
-            Err(aoc::Error::ParseFailed)
+            Err(line
+                .error("`Sensor at x=<n>, y=<n>: closest beacon is at x=<n>, y=<n>`")
+                .into())
         }
     }
 
     fn extents(&self) -> &SensorExtents {
         &self.extents
     }
+
+    /// Whether `point` is at least as close to the sensor as its beacon.
+    fn covers(&self, point: Point2D) -> bool {
+        let extents = self.extents();
+        let distance = extents.max_y - extents.center_y;
+        let center_x = extents.min_x + distance;
+        (point.0 - center_x).abs() + (point.1 - extents.center_y).abs() <= distance
+    }
 }
 
-#[derive(Debug, Clone)]
-struct IntSpan {
-    ranges: Vec<(i64, i64)>,
+/// Rows to inspect; the worked example uses a much smaller area than the real puzzle.
+#[derive(Debug, Clone, Copy)]
+pub struct Params {
+    pub part1_y: i64,
+    pub part2_extent: i64,
 }
-impl IntSpan {
-    fn new() -> Self {
-        Self { ranges: Vec::new() }
-    }
 
-    fn add_range(&mut self, min: i64, max: i64) {
-        if self.ranges.is_empty() {
-            self.ranges.push((min, max));
-        } else {
-            // find insertion point
-            let insert_pos = match self.ranges.binary_search_by(|v| v.0.cmp(&min)) {
-                Ok(v) | Err(v) => v,
-            };
-            self.ranges.insert(insert_pos, (min, max));
-            // now fixup
-            let mut cur_i = insert_pos;
-            while cur_i < self.ranges.len() {
-                // did we just encroach on the next element?
-                if cur_i + 1 < self.ranges.len()
-                    && self.ranges[cur_i + 1].0 <= self.ranges[cur_i].1 + 1
-                {
-                    // extend ourselves and delete the next element
-                    self.ranges[cur_i].1 = cmp::max(self.ranges[cur_i].1, self.ranges[cur_i + 1].1);
-                    self.ranges.remove(cur_i + 1);
-                } else if cur_i > 0 && self.ranges[cur_i - 1].1 + 1 >= self.ranges[cur_i].0 {
-                    // extend previous element and delete ourselves
-                    self.ranges[cur_i - 1].1 =
-                        cmp::max(self.ranges[cur_i - 1].1, self.ranges[cur_i].1);
-                    self.ranges.remove(cur_i);
-                } else {
-                    cur_i += 1;
-                }
-            }
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_y: 2_000_000,
+            part2_extent: 4_000_000,
         }
     }
+}
 
-    fn len(&self) -> usize {
-        self.ranges.len()
-    }
+/// Part 2's answer when the search area doesn't have exactly one position a beacon could be in.
+pub const NO_DISTRESS_BEACON: i64 = -1;
+
+fn tuning_frequency(point: Point2D) -> i64 {
+    point.0 * 4_000_000 + point.1
 }
 
-fn solve(filename: &str, part1_y: i64, part2_extent: i64) -> aoc::Result<(i64, i64)> {
-    let mut timer = CodeTimer::new();
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u32 = 15;
 
-    let reader = aoc::file(filename)?;
-    let sensors: Vec<Sensor> = reader
-        .lines()
-        .map(Sensor::parse)
-        .collect::<aoc::Result<_>>()?;
+    type Input = Vec<Sensor>;
+    type Params = Params;
+    type Answer1 = u64;
+    type Answer2 = i64;
 
-    timer.split("parse");
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        NumberedLines::new(Self::DAY, reader)
+            .map(Sensor::parse)
+            .collect()
+    }
 
-    let part1 = {
+    fn part1(sensors: &Self::Input, params: &Params) -> u64 {
         let mut spans = IntSpan::new();
-        for sensor in &sensors {
+        for sensor in sensors {
             let extents = sensor.extents();
-            if extents.is_y_within(part1_y) {
-                let x_extents_at = extents.x_extents_at(part1_y).unwrap();
+            if extents.is_y_within(params.part1_y) {
+                let x_extents_at = extents.x_extents_at(params.part1_y).unwrap();
                 //println!("adding extent {:?}",x_extents_at);
                 spans.add_range(x_extents_at.0, x_extents_at.1);
             }
         }
-        spans.ranges.into_iter().map(|span| span.1 - span.0).sum()
-    };
-
-    timer.split("part1");
-
-    // debugging blah
-    /*{
-        for y in 0..=part2_extent {
-            let mut row : Vec<char> = vec!['.'; (part2_extent as usize+1)];
-            for sensor in &sensors {
-                let extents = sensor.extents();
-                if extents.is_y_within(y) {
-                    let x_extents_at = extents.x_extents_at(y).unwrap();
-                    for x in 0..=part2_extent {
-                        if x >= x_extents_at.0 && x <= x_extents_at.1 {
-                            row[x as usize] = '#';
-                        }
-                    }
-                }
+        // positions holding a known beacon can still have a beacon
+        for sensor in sensors {
+            if sensor.beacon.1 == params.part1_y {
+                spans.remove(sensor.beacon.0);
             }
-            println!("{}",row.into_iter().collect::<String>());
         }
-        timer.split("debug");
-    }*/
+        spans.count()
+    }
 
-    let part2 = (0..=part2_extent)
-        .into_par_iter()
-        .find_map_any(|y| {
-            let mut spans = IntSpan::new();
-            for sensor in &sensors {
-                let extents = sensor.extents();
-                if let Some(x_extents_at) = extents.x_extents_at(y) {
-                    //println!("adding extent {:?}",x_extents_at);
-                    spans.add_range(x_extents_at.0, x_extents_at.1);
+    /// The tuning frequency of the uncovered position, or [`NO_DISTRESS_BEACON`] if there is none.
+    /// Only the first row with a gap is searched, and a row with several also gives
+    /// [`NO_DISTRESS_BEACON`].
+    fn part2(sensors: &Self::Input, params: &Params) -> i64 {
+        (0..=params.part2_extent)
+            .into_par_iter()
+            .find_map_first(|y| {
+                let mut spans = IntSpan::new();
+                for sensor in sensors {
+                    let extents = sensor.extents();
+                    if let Some(x_extents_at) = extents.x_extents_at(y) {
+                        //println!("adding extent {:?}",x_extents_at);
+                        spans.add_range(x_extents_at.0, x_extents_at.1);
+                    }
                 }
-            }
-            if spans.len() > 1 {
-                assert!(spans.len() == 2);
-
-                Some((spans.ranges[0].1 + 1) * 4_000_000 + y)
-            } else {
-                None
-            }
-        })
-        .unwrap();
+                let gaps = spans.complement(0, params.part2_extent);
+                let gap = gaps.ranges().first()?;
+                Some(if gaps.count() == 1 {
+                    tuning_frequency(Point2D(gap.0, y))
+                } else {
+                    NO_DISTRESS_BEACON
+                })
+            })
+            .unwrap_or(NO_DISTRESS_BEACON)
+    }
+}
 
-    timer.stop("part2");
+/// `size` sensors, leaving exactly one spot in the search area uncovered.
+///
+/// Four sensors sit diagonally out from the spot, far enough away that between them they reach
+/// every other position in the search area. The rest are scattered about with ranges that stop
+/// short of it.
+impl Generate for Day15 {
+    const SIZE: usize = 30;
+
+    fn generate(rng: &mut Random, size: usize, params: &Params) -> String {
+        let extent = params.part2_extent;
+        let hidden = Point2D(rng.gen_range(0..=extent), rng.gen_range(0..=extent));
+        let far = extent + 1;
+
+        // each just misses the spot, with a beacon right next to it
+        let mut sensors: Vec<(Point2D, Point2D)> = [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+            .iter()
+            .map(|&(dx, dy)| {
+                (
+                    Point2D(hidden.0 + dx * far, hidden.1 + dy * far),
+                    Point2D(hidden.0 + dx, hidden.1),
+                )
+            })
+            .collect();
 
-    Ok((part1, part2))
+        while sensors.len() < size {
+            let sensor = Point2D(rng.gen_range(0..=extent), rng.gen_range(0..=extent));
+            let reach = (sensor - hidden).manhattan_distance();
+            if reach < 2 {
+                continue;
+            }
+            let range = rng.gen_range(1..reach);
+            let dx = rng.gen_range(-range..=range);
+            let dy = (range - dx.abs()) * if rng.gen() { 1 } else { -1 };
+            sensors.push((sensor, Point2D(sensor.0 + dx, sensor.1 + dy)));
+        }
+        sensors.shuffle(rng);
+
+        join_lines(sensors.iter().map(|(sensor, beacon)| {
+            format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+                sensor.0, sensor.1, beacon.0, beacon.1
+            )
+        }))
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day15", 2_000_000, 4_000_000)?;
+/// Checks every cell against every sensor, so only the worked example's search area is small
+/// enough.
+impl Reference for Day15 {
+    fn reference_params() -> Params {
+        Params {
+            part1_y: 10,
+            part2_extent: 20,
+        }
+    }
 
-    println!("{}", part1);
-    println!("{}", part2);
+    fn reference1(sensors: &Self::Input, params: &Params) -> Option<u64> {
+        let min_x = sensors.iter().map(|sensor| sensor.extents().min_x).min()?;
+        let max_x = sensors.iter().map(|sensor| sensor.extents().max_x).max()?;
+        let ruled_out = (min_x..=max_x).filter(|&x| {
+            let point = Point2D(x, params.part1_y);
+            sensors.iter().any(|sensor| sensor.covers(point))
+                && sensors.iter().all(|sensor| sensor.beacon != point)
+        });
+        u64::try_from(ruled_out.count()).ok()
+    }
 
-    Ok(())
+    fn reference2(sensors: &Self::Input, params: &Params) -> Option<i64> {
+        let extent = params.part2_extent;
+        let uncovered = |y| {
+            (0..=extent)
+                .map(move |x| Point2D(x, y))
+                .filter(|&point| !sensors.iter().any(|sensor| sensor.covers(point)))
+                .collect::<Vec<_>>()
+        };
+        match (0..=extent)
+            .map(uncovered)
+            .find(|row| !row.is_empty())
+            .as_deref()
+        {
+            Some(&[point]) => Some(tuning_frequency(point)),
+            _ => Some(NO_DISTRESS_BEACON),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{solve, IntSpan};
+    use super::{Day15, Params, NO_DISTRESS_BEACON};
+    use crate::{
+        error::Error,
+        generate::{Generate, Random},
+        solution::{solve, solve_with, Solution},
+    };
+    use adventlib::aoc;
+    use rand::SeedableRng;
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day15", 10, 20).unwrap();
+        let params = Params {
+            part1_y: 10,
+            part2_extent: 20,
+        };
+        let (part1, part2) =
+            solve_with::<Day15, _>(aoc::file("inputs-sample/day15").unwrap(), &params).unwrap();
 
         assert_eq!(part1, 26);
         assert_eq!(part2, 56000011);
     }
 
     #[test]
-    fn intspan() {
-        let mut span = IntSpan::new();
-
-        assert_eq!(span.len(), 0);
-
-        // 5-10
-        span.add_range(5, 10);
-        assert_eq!(span.ranges, vec![(5, 10)]);
-
-        // 5-10, 20-30
-        span.add_range(20, 30);
-        assert_eq!(span.ranges, vec![(5, 10), (20, 30)]);
-
-        // 1-10, 20-30
-        span.add_range(1, 5);
-        assert_eq!(span.ranges, vec![(1, 10), (20, 30)]);
-
-        // 1-30 ( now let's try subsuming the entire range )
-        span.add_range(1, 21);
-        assert_eq!(span.ranges, vec![(1, 30)]);
-
-        // 1-50
-        span.add_range(29, 50);
-        assert_eq!(span.ranges, vec![(1, 50)]);
-
-        // 1-50,75-100
-        span.add_range(75, 100);
-        assert_eq!(span.ranges, vec![(1, 50), (75, 100)]);
-
-        // 1-50,60-70,75-100
-        span.add_range(60, 70);
-        assert_eq!(span.ranges, vec![(1, 50), (60, 70), (75, 100)]);
-
-        // 1-100
-        span.add_range(50, 75);
-        assert_eq!(span.ranges, vec![(1, 100)]);
-
-        // 1-200
-        span.add_range(101, 200);
-        assert_eq!(span.ranges, vec![(1, 200)]);
-
-        // (-200)-200
-        span.add_range(-200, 0);
-        assert_eq!(span.ranges, vec![(-200, 200)]);
+    fn malformed() {
+        for (input, line) in [
+            ("Sensor at x=2, y=18: closest beacon is at x=-2\n", 1),
+            (
+                "Sensor at x=2, y=18: closest beacon is at x=-2, y=15\n\
+                 Sensor at x=9223372036854775807, y=0: closest beacon is at x=-2, y=15\n",
+                2,
+            ),
+        ] {
+            match solve::<Day15, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
     }
 
     #[test]
-    fn intspan_real() {
-        let mut span = IntSpan::new();
-
-        assert_eq!(span.len(), 0);
-
-        // (-12)-2
-        span.add_range(-12, 2);
-        assert_eq!(span.ranges, vec![(-12, 2)]);
-
-        // (-12)-2,5-13
-        span.add_range(5, 13);
-        assert_eq!(span.ranges, vec![(-12, 2), (5, 13)]);
-
-        // (-12)-13
-        span.add_range(-5, 13);
-        assert_eq!(span.ranges, vec![(-12, 13)]);
-
-        // (-12)-13
-        span.add_range(-3, 3);
-        assert_eq!(span.ranges, vec![(-12, 13)]);
+    fn no_single_gap() {
+        let params = Params {
+            part1_y: 10,
+            part2_extent: 20,
+        };
+        for input in [
+            // covers everything
+            "Sensor at x=10, y=10: closest beacon is at x=30, y=30\n",
+            // covers nothing
+            "Sensor at x=100, y=100: closest beacon is at x=101, y=100\n",
+            // misses the two bottom corners
+            "Sensor at x=10, y=0: closest beacon is at x=10, y=29\n",
+        ] {
+            let (_, part2) = solve_with::<Day15, _>(input.as_bytes(), &params).unwrap();
+            assert_eq!(part2, NO_DISTRESS_BEACON, "{}", input);
+        }
+    }
 
-        // (-12)-25
-        span.add_range(9, 25);
-        assert_eq!(span.ranges, vec![(-12, 25)]);
+    #[test]
+    fn generated() {
+        let params = Params {
+            part1_y: 10,
+            part2_extent: 20,
+        };
+        for seed in 0..5 {
+            let input = Day15::generate(&mut Random::seed_from_u64(seed), 10, &params);
+            let sensors = Day15::parse(input.as_bytes()).unwrap();
+            let covered = |x: i64, y: i64| {
+                sensors.iter().any(|sensor| {
+                    sensor
+                        .extents()
+                        .x_extents_at(y)
+                        .is_some_and(|(min, max)| (min..=max).contains(&x))
+                })
+            };
 
-        // (-12)-25
-        span.add_range(7, 17);
-        assert_eq!(span.ranges, vec![(-12, 25)]);
+            let uncovered = (0..=20)
+                .flat_map(|y| (0..=20).map(move |x| (x, y)))
+                .filter(|&(x, y)| !covered(x, y))
+                .count();
+            assert_eq!(uncovered, 1, "{}", input);
+        }
     }
 }
@@ -1,8 +1,28 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
 #![allow(clippy::unreadable_literal)]
-use adventlib::aoc;
-use std::{cmp, collections::HashMap, io::read_to_string};
+use crate::{
+    cycle::find_cycle,
+    error::Result,
+    export::{Export, Image, Rgb, BLACK},
+    generate::{Generate, Random},
+    input::NumberedLines,
+    reference::Reference,
+    solution::Solution,
+    visualize::{Animator, Frame, Visualize},
+};
+use rand::Rng;
+use std::{cmp, io, io::BufRead, ops::Range};
+
+/// Rows of the chamber shown while visualizing.
+const VIEW_ROWS: usize = 30;
+
+/// Fewest jets a generated input has; short patterns can stack rocks without ever filling a row,
+/// and part 2 only finds its cycle once rows below the top are filled and discarded.
+const MIN_JETS: usize = 1000;
+
+/// Rows from the top of the tower that part 2 compares when looking for a cycle. Towers that close
+/// rows off every so often never keep this many above the last one; those that don't, like one
+/// where every jet blows the same way, would otherwise never repeat.
+const CYCLE_ROWS: usize = 128;
 
 const PIECES: [&[u8]; 5] = [
     &[0b00011110],
@@ -77,22 +97,32 @@ impl PieceBag {
     }
 }
 
-enum PlayfieldMoveState {
+pub enum PlayfieldMoveState {
     Completed,
     Dropped,
 }
 
+/// The chamber rocks fall into, fed one jet of gas at a time through [`Playfield::process_move`].
 #[derive(Debug)]
-struct Playfield {
+pub struct Playfield {
     field: Vec<u8>,
     first_empty: usize,
     bag: PieceBag,
     current_piece: Piece,
     current_height: usize,
     offset: usize,
+    /// Rows discarded from the bottom of `field`, if asked to keep them.
+    archive: Option<Vec<u8>>,
 }
+impl Default for Playfield {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Playfield {
-    fn new() -> Self {
+    #[must_use]
+    pub fn new() -> Self {
         let mut bag = PieceBag::new();
         let piece = bag.next();
         let mut rv = Self {
@@ -102,12 +132,21 @@ impl Playfield {
             current_piece: piece,
             current_height: 3 + 1,
             offset: 0,
+            archive: None,
         };
         rv.extend_if_needed();
         assert!(rv.piece_can_exist_at(rv.current_height));
         rv
     }
 
+    /// A playfield that keeps every row of the tower, rather than only those that still matter.
+    fn with_archive() -> Self {
+        Self {
+            archive: Some(Vec::new()),
+            ..Self::new()
+        }
+    }
+
     fn piece_can_exist_at(&self, height: usize) -> bool {
         assert!(height - self.offset + self.current_piece.0.len() < self.field.len());
         (0..self.current_piece.0.len())
@@ -130,13 +169,16 @@ impl Playfield {
         }
         self.first_empty = cmp::max(self.first_empty, height + self.current_piece.0.len());
 
-        // check all pieces to see if there's a point that's closed off
+        // check all pieces to see if there's a point that's closed off; the two rows below it are
+        // kept, so a pair just above the last one closed off leaves nothing to discard
         for i in (height..self.first_empty - 1).rev() {
-            if i - self.offset >= 1
-                && i >= self.offset
+            if i >= self.offset + 2
                 && self.field[i - self.offset] | self.field[i - self.offset + 1] == 0b1111111
             {
-                self.field.drain(0..(i - self.offset - 2));
+                let discarded = self.field.drain(0..(i - self.offset - 2));
+                if let Some(archive) = &mut self.archive {
+                    archive.extend(discarded);
+                }
                 self.offset = i - 2;
                 break;
             }
@@ -146,7 +188,14 @@ impl Playfield {
         self.current_height = self.first_empty + 3;
     }
 
-    fn process_move(&mut self, action: u8) -> PlayfieldMoveState {
+    /// Height of the tower of settled rocks.
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.first_empty - 1
+    }
+
+    /// Pushes the falling rock with a `<` or `>` jet, then lets it fall one unit.
+    pub fn process_move(&mut self, action: u8) -> PlayfieldMoveState {
         assert!(self.current_height > 0);
         assert!(self.piece_can_exist_at(self.current_height));
         if self.current_piece.shift_using(action) && !self.piece_can_exist_at(self.current_height) {
@@ -162,113 +211,346 @@ impl Playfield {
         }
     }
 
-    #[allow(dead_code)]
-    fn dump(&self) {
-        println!("-------------");
-        for (i, v) in self.field.iter().enumerate().rev() {
-            let v = if i >= self.current_height
-                && i < self.current_height + self.current_piece.0.len()
-            {
-                *v | self.current_piece.0[i - self.current_height]
-            } else {
-                *v
-            };
-            if i + self.offset == self.current_height {
-                println!("{:8} {:08b} --- cur", i + self.offset, v);
-            } else if i + self.offset == self.first_empty {
-                println!("{:8} {:08b} --- empty", i + self.offset, v);
+    /// Heights of the top `rows` rows of the chamber, stopping at the floor or whatever has been
+    /// discarded.
+    fn view(&self, rows: usize) -> Range<usize> {
+        let top = self.current_height + self.current_piece.0.len();
+        cmp::max(top.saturating_sub(rows), self.offset)..top
+    }
+
+    /// What is in `column` of row `height`: `@` for the falling rock, `#` for settled rock, `-` for
+    /// the floor and `.` for air.
+    fn cell(&self, height: usize, column: usize) -> char {
+        let bit = 1 << (6 - column);
+        let falling = height
+            .checked_sub(self.current_height)
+            .and_then(|i| self.current_piece.0.get(i))
+            .copied()
+            .unwrap_or(0);
+
+        if height == 0 {
+            '-'
+        } else if falling & bit != 0 {
+            '@'
+        } else if self.field[height - self.offset] & bit != 0 {
+            '#'
+        } else {
+            '.'
+        }
+    }
+
+    /// The top `rows` rows of the chamber drawn the way the puzzle does.
+    fn render(&self, rows: usize) -> String {
+        let mut out = String::new();
+        for height in self.view(rows).rev() {
+            if height == 0 {
+                out.push_str("+-------+\n");
             } else {
-                println!("{:8} {:08b}", i + self.offset, v);
+                out.push('|');
+                out.extend((0..7).map(|column| self.cell(height, column)));
+                out.push_str("|\n");
             }
         }
+        out
+    }
+
+    /// Every settled row from the floor up; only complete for a playfield made
+    /// [`with_archive`](Self::with_archive).
+    fn tower(&self) -> Vec<u8> {
+        let mut rows = self.archive.clone().unwrap_or_default();
+        rows.extend_from_slice(&self.field[0..self.first_empty - self.offset]);
+        rows
+    }
+}
+
+fn colour(cell: char) -> Rgb {
+    match cell {
+        '@' => [230, 120, 40],
+        '#' => [200, 200, 200],
+        '-' => [110, 80, 50],
+        _ => BLACK,
     }
 }
 
-fn solve(filename: &str) -> aoc::Result<(usize, usize)> {
-    let moves = read_to_string(aoc::file(filename)?)?.into_bytes();
-    let mut idx = 0;
-    let mut timer = aoc::CodeTimer::new();
-
-    let mut playfield = Playfield::new();
-    let mut n_dropped: usize = 0;
-    while n_dropped < 2022 {
-        match playfield.process_move(moves[idx]) {
-            PlayfieldMoveState::Completed => {}
-            PlayfieldMoveState::Dropped => n_dropped += 1,
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u32 = 17;
+
+    type Input = Vec<u8>;
+    type Params = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let expected = "a line of `<` and `>` jets";
+        let line = NumberedLines::new(Self::DAY, reader).expect_next(expected)?;
+        if line.text.is_empty() || !line.text.bytes().all(|b| b == b'<' || b == b'>') {
+            return Err(line.error(expected).into());
         }
-        idx = (idx + 1) % moves.len();
+
+        Ok(line.text.into_bytes())
     }
 
-    timer.split("part1");
-    let part1 = playfield.first_empty - 1;
+    fn part1(moves: &Self::Input, _params: &()) -> usize {
+        let mut idx = 0;
+        let mut playfield = Playfield::new();
+        let mut n_dropped: usize = 0;
+        while n_dropped < 2022 {
+            match playfield.process_move(moves[idx]) {
+                PlayfieldMoveState::Completed => {}
+                PlayfieldMoveState::Dropped => n_dropped += 1,
+            }
+            idx = (idx + 1) % moves.len();
+        }
+
+        playfield.height()
+    }
+
+    fn part2(moves: &Self::Input, _params: &()) -> usize {
+        let wanted_dropped = 1_000_000_000_000;
+        let mut state = (Playfield::new(), 0);
+
+        let cycle = find_cycle(
+            &mut state,
+            wanted_dropped,
+            |(playfield, idx)| loop {
+                let outcome = playfield.process_move(moves[*idx]);
+                *idx = (*idx + 1) % moves.len();
+                if let PlayfieldMoveState::Dropped = outcome {
+                    break;
+                }
+            },
+            |(playfield, idx)| {
+                let top = playfield.first_empty - playfield.offset - 1;
+                (
+                    *idx,
+                    playfield.bag.0,
+                    playfield.field[top.saturating_sub(CYCLE_ROWS)..top].to_vec(),
+                )
+            },
+            |(playfield, _)| playfield.height() as u64,
+        );
+
+        match cycle {
+            Some(cycle) => usize::try_from(cycle.extrapolate(wanted_dropped, |&height| height))
+                .expect("tower too tall"),
+            None => state.0.height(),
+        }
+    }
+}
+
+/// Drops the 2022 rocks from part 1, one frame per jet of gas.
+impl Visualize for Day17 {
+    fn visualize(moves: &Self::Input, _params: &(), animator: &mut Animator<'_>) -> io::Result<()> {
+        let mut idx = 0;
+        let mut playfield = Playfield::new();
+        let mut n_dropped: usize = 0;
+        while n_dropped < 2022 {
+            if let PlayfieldMoveState::Dropped = playfield.process_move(moves[idx]) {
+                n_dropped += 1;
+            }
+            idx = (idx + 1) % moves.len();
+            animator.frame(|| Frame::Text(playfield.render(VIEW_ROWS)))?;
+        }
+
+        animator.finish(&Frame::Text(playfield.render(VIEW_ROWS)))
+    }
+}
 
-    let mut seen: HashMap<(usize, Vec<u8>), (usize, usize)> = HashMap::new();
-    let mut height_offset = None;
-    let wanted_dropped = 1_000_000_000_000;
-    while n_dropped < wanted_dropped {
-        match playfield.process_move(moves[idx]) {
-            PlayfieldMoveState::Completed => {}
-            PlayfieldMoveState::Dropped => {
+/// The whole tower once part 1's rocks have settled, or the top of the chamber as they fall.
+impl Export for Day17 {
+    fn snapshot(moves: &Self::Input, _params: &()) -> Image {
+        let mut idx = 0;
+        let mut playfield = Playfield::with_archive();
+        let mut n_dropped: usize = 0;
+        while n_dropped < 2022 {
+            if let PlayfieldMoveState::Dropped = playfield.process_move(moves[idx]) {
                 n_dropped += 1;
+            }
+            idx = (idx + 1) % moves.len();
+        }
+
+        let tower = playfield.tower();
+        let mut image = Image::new(7, tower.len(), BLACK);
+        for (height, row) in tower.iter().enumerate() {
+            for column in 0..7 {
+                let cell = match (height, row & (1 << (6 - column))) {
+                    (0, _) => '-',
+                    (_, 0) => '.',
+                    _ => '#',
+                };
+                image.set((tower.len() - 1 - height, column), colour(cell));
+            }
+        }
+        image
+    }
 
-                if height_offset.is_none() {
-                    let state = (
-                        idx,
-                        playfield.field[0..playfield.first_empty - playfield.offset - 1].to_vec(),
-                    );
-                    if let Some(benchmark) = seen.get(&state) {
-                        /*println!(
-                            " at {} {}: current {} {}, seen {} {}",
-                            idx,
-                            playfield.bag.0,
-                            playfield.first_empty - 1,
-                            n_dropped,
-                            benchmark.0,
-                            benchmark.1
-                        );*/
-                        let drop_delta = n_dropped - benchmark.1;
-                        let height_delta = playfield.first_empty - 1 - benchmark.0;
-                        //println!(" in {} drops we grew {}", drop_delta, height_delta);
-                        let drops_remain = wanted_dropped - n_dropped;
-                        let whole_cycles = drops_remain / drop_delta;
-                        /*println!(
-                            " we need {} more drops, so {} cycles",
-                            drops_remain, whole_cycles
-                        );*/
-                        n_dropped += drop_delta * whole_cycles;
-                        height_offset = Some(height_delta * whole_cycles);
-                    } else {
-                        seen.insert(state, (playfield.first_empty - 1, n_dropped));
-                    }
+    fn frames(
+        moves: &Self::Input,
+        _params: &(),
+        every: usize,
+        frame: &mut dyn FnMut(&Image) -> Result<()>,
+    ) -> Result<()> {
+        let window = |playfield: &Playfield| {
+            let mut image = Image::new(7, VIEW_ROWS, BLACK);
+            for (row, height) in playfield.view(VIEW_ROWS).rev().enumerate() {
+                for column in 0..7 {
+                    image.set((row, column), colour(playfield.cell(height, column)));
                 }
             }
+            image
+        };
+
+        let mut idx = 0;
+        let mut playfield = Playfield::new();
+        let mut n_dropped: usize = 0;
+        while n_dropped < 2022 {
+            if let PlayfieldMoveState::Dropped = playfield.process_move(moves[idx]) {
+                n_dropped += 1;
+                if n_dropped.is_multiple_of(every) {
+                    frame(&window(&playfield))?;
+                }
+            }
+            idx = (idx + 1) % moves.len();
         }
-        idx = (idx + 1) % moves.len();
+        frame(&window(&playfield))
     }
-    let part2 = playfield.first_empty - 1 + height_offset.unwrap_or(0);
-    timer.stop("part2");
+}
 
-    Ok((part1, part2))
+/// `size` jets of gas, though never fewer than [`MIN_JETS`].
+impl Generate for Day17 {
+    const SIZE: usize = 10091;
+
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let mut jets: String = (0..size.max(MIN_JETS))
+            .map(|_| if rng.gen() { '<' } else { '>' })
+            .collect();
+        jets.push('\n');
+        jets
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day17")?;
+/// Height of the tower after dropping `rocks` rocks into a chamber that keeps every row, checking
+/// each move against the whole tower.
+fn untrimmed_height(moves: &[u8], rocks: usize) -> usize {
+    let fits = |tower: &[u8], piece: &Piece, bottom: usize| {
+        piece.0.iter().enumerate().all(|(i, row)| {
+            tower
+                .get(bottom + i)
+                .is_none_or(|settled| settled & row == 0)
+        })
+    };
 
-    println!("{}", part1);
-    println!("{}", part2);
+    let mut tower = vec![0b1111111];
+    let mut jets = moves.iter().cycle();
+    for shape in PIECES.iter().cycle().take(rocks) {
+        let mut piece = Piece::new(shape);
+        let mut bottom = tower.len() + 3;
+        loop {
+            let jet = *jets.next().expect("jets repeat forever");
+            if piece.shift_using(jet) && !fits(&tower, &piece, bottom) {
+                piece.unshift_using(jet);
+            }
+            if !fits(&tower, &piece, bottom - 1) {
+                break;
+            }
+            bottom -= 1;
+        }
+
+        for (i, row) in piece.0.iter().enumerate() {
+            if bottom + i == tower.len() {
+                tower.push(0);
+            }
+            tower[bottom + i] |= row;
+        }
+    }
+    tower.len() - 1
+}
 
-    Ok(())
+/// Part 1 without discarding the rows below a sealed pair. A trillion rocks won't fit, so there's
+/// no reference for part 2.
+impl Reference for Day17 {
+    fn reference1(moves: &Self::Input, _params: &()) -> Option<usize> {
+        Some(untrimmed_height(moves, 2022))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::{untrimmed_height, Day17, Piece, PIECES};
+    use crate::{
+        reference::{compare, Options},
+        solution::{solve, solve_file},
+    };
+    use proptest::prelude::*;
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day17").unwrap();
+        let (part1, part2) = solve_file::<Day17>("inputs-sample/day17").unwrap();
 
         assert_eq!(part1, 3068);
         assert_eq!(part2, 1514285714288);
     }
+
+    #[test]
+    fn trimming() {
+        // seed 11 seals a pair of rows just above the last trim
+        let options = Options {
+            seeds: 20,
+            size: None,
+        };
+        assert_eq!(compare::<Day17>(&options).unwrap(), None);
+    }
+
+    #[test]
+    fn one_sided() {
+        // rows never close off, so the tower only repeats near the top
+        for jets in ["<", ">"] {
+            let (part1, part2) = solve::<Day17, _>(jets.as_bytes()).unwrap();
+            let moves = jets.as_bytes();
+            assert_eq!(part1, untrimmed_height(moves, 2022), "{}", jets);
+
+            // one jet and five rocks, so it's settled into repeating every five rocks by now
+            let (start, period) = (1000, 5);
+            let gain = untrimmed_height(moves, start + period) - untrimmed_height(moves, start);
+            let rocks = 1_000_000_000_000;
+            assert_eq!(
+                part2,
+                untrimmed_height(moves, start) + (rocks - start) / period * gain,
+                "{}",
+                jets
+            );
+        }
+    }
+
+    fn shifts() -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec(prop_oneof![Just(b'<'), Just(b'>')], 0..16)
+    }
+
+    proptest! {
+        #[test]
+        fn shifts_are_reversible(kind in 0..PIECES.len(), before in shifts(), ops in shifts()) {
+            let mut piece = Piece::new(PIECES[kind]);
+            for op in before {
+                piece.shift_using(op);
+            }
+
+            for op in ops {
+                let was = piece.clone();
+                if piece.shift_using(op) {
+                    prop_assert!(piece.unshift_using(op));
+                    prop_assert_eq!(&piece.0, &was.0);
+                    prop_assert!(piece.shift_using(op));
+                } else {
+                    prop_assert_eq!(&piece.0, &was.0);
+                }
+
+                // the piece never leaves the seven columns or loses a cell
+                prop_assert!(piece.0.iter().all(|row| row & 0b10000000 == 0));
+                let cells = |rows: &[u8]| rows.iter().map(|row| row.count_ones()).sum::<u32>();
+                prop_assert_eq!(cells(&piece.0), cells(PIECES[kind]));
+            }
+        }
+    }
 }
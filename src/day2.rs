@@ -1,6 +1,10 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+};
+use rand::Rng;
 use std::io::BufRead;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -11,12 +15,12 @@ enum Move {
 }
 
 impl Move {
-    fn parse(s: &str) -> Self {
+    fn parse(s: &str) -> Option<Self> {
         match s {
-            "A" | "X" => Self::Rock,
-            "B" | "Y" => Self::Paper,
-            "C" | "Z" => Self::Scissors,
-            _ => unimplemented!(),
+            "A" | "X" => Some(Self::Rock),
+            "B" | "Y" => Some(Self::Paper),
+            "C" | "Z" => Some(Self::Scissors),
+            _ => None,
         }
     }
 
@@ -46,26 +50,31 @@ impl GameResult {
         }
     }
 
-    fn parse(s: &str) -> Self {
+    fn parse(s: &str) -> Option<Self> {
         match s {
-            "X" => Self::Player1,
-            "Y" => Self::Tie,
-            "Z" => Self::Player2,
-            _ => unimplemented!(),
+            "X" => Some(Self::Player1),
+            "Y" => Some(Self::Tie),
+            "Z" => Some(Self::Player2),
+            _ => None,
         }
     }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-struct Game(Move, Move, GameResult);
+pub struct Game(Move, Move, GameResult);
 
 impl Game {
-    fn parse(s: &str) -> Self {
-        Self(
-            Move::parse(&s[0..1]),
-            Move::parse(&s[2..3]),
-            GameResult::parse(&s[2..3]),
-        )
+    fn parse(s: &str) -> Option<Self> {
+        let (theirs, ours) = s.split_once(' ')?;
+        if !matches!(theirs, "A" | "B" | "C") {
+            return None;
+        }
+
+        Some(Self(
+            Move::parse(theirs)?,
+            Move::parse(ours)?,
+            GameResult::parse(ours)?,
+        ))
     }
 
     fn result(self) -> GameResult {
@@ -108,45 +117,86 @@ impl Game {
     }
 }
 
-fn solve(filename: &str) -> aoc::Result<(u32, u32)> {
-    let reader = aoc::file(filename)?;
+pub struct Day2;
 
-    let moves: Vec<_> = reader
-        .lines()
-        .map(|line| Game::parse(&line.unwrap()))
-        .collect();
+impl Solution for Day2 {
+    const DAY: u32 = 2;
+
+    type Input = Vec<Game>;
+    type Params = ();
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        NumberedLines::new(Self::DAY, reader)
+            .map(|line| {
+                let line = line?;
+                Game::parse(&line.text).ok_or_else(|| line.error("`<A|B|C> <X|Y|Z>`").into())
+            })
+            .collect()
+    }
 
-    Ok((
+    fn part1(moves: &Self::Input, _params: &()) -> u32 {
         moves
             .iter()
             .map(|m| m.result().score(GameResult::Player2) + m.1.score())
-            .sum::<u32>(),
+            .sum()
+    }
+
+    fn part2(moves: &Self::Input, _params: &()) -> u32 {
         moves
             .iter()
             .map(|m| m.with_requred_result())
             .map(|m| m.result().score(GameResult::Player2) + m.1.score())
-            .sum::<u32>(),
-    ))
+            .sum()
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day2")?;
+/// `size` rounds.
+impl Generate for Day2 {
+    const SIZE: usize = 2500;
 
-    println!("{}", part1);
-    println!("{}", part2);
-
-    Ok(())
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        join_lines((0..size.max(1)).map(|_| {
+            let theirs = char::from(b'A' + rng.gen_range(0..3));
+            let ours = char::from(b'X' + rng.gen_range(0..3));
+            format!("{} {}", theirs, ours)
+        }))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day2;
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day2").unwrap();
+        let (part1, part2) = solve_file::<Day2>("inputs-sample/day2").unwrap();
 
         assert_eq!(part1, 15);
         assert_eq!(part2, 12);
     }
+
+    #[test]
+    fn in_memory() {
+        let (part1, part2) = solve::<Day2, _>("A Y\nB X\nC Z\n".as_bytes()).unwrap();
+
+        assert_eq!(part1, 15);
+        assert_eq!(part2, 12);
+    }
+
+    #[test]
+    fn malformed() {
+        match solve::<Day2, _>("A Y\nB W\n".as_bytes()) {
+            Err(Error::Parse(e)) => {
+                assert_eq!(e.line, 2);
+                assert_eq!(e.text.as_deref(), Some("B W"));
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
 }
@@ -1,81 +1,118 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+};
+use rand::Rng;
 use std::io::BufRead;
 
-fn convert(number: &str) -> i64 {
-    let mut mul = 1;
-    let mut result = 0;
-    for val in number.as_bytes().iter().rev() {
-        result += mul
-            * match val {
-                b'-' => -1,
-                b'=' => -2,
-                num => i64::from(num - b'0'),
-            };
-        mul *= 5;
-    }
+/// Converts a SNAFU number to decimal, or `None` if it is malformed or doesn't fit in an `i64`.
+#[must_use]
+pub fn try_convert(number: &str) -> Option<i64> {
+    // most significant digit first, so nothing is ever scaled past the final value
+    number.bytes().try_fold(0i64, |result, val| {
+        let digit = match val {
+            b'-' => -1,
+            b'=' => -2,
+            b'0'..=b'2' => i64::from(val - b'0'),
+            _ => return None,
+        };
+        result.checked_mul(5)?.checked_add(digit)
+    })
+}
 
-    result
+/// Converts a SNAFU number to decimal.
+#[must_use]
+pub fn convert(number: &str) -> i64 {
+    try_convert(number).expect("a SNAFU number that fits in an i64")
 }
 
-#[allow(clippy::cast_sign_loss)]
-fn unconvert(mut number: i64) -> String {
-    let mut out: Vec<u8> = Vec::new();
-    let mut borrowed = 0;
-
-    while number > 0 || borrowed > 0 {
-        let cur_val = (number % 5) as u8 + borrowed;
-        if cur_val > 2 {
-            borrowed = 1;
-            if cur_val == 3 {
-                out.push(b'=');
-            } else if cur_val == 4 {
-                out.push(b'-');
-            } else if cur_val == 5 {
-                out.push(b'0');
-            } else {
-                unreachable!();
-            }
-        } else {
-            borrowed = 0;
-            out.push(cur_val + b'0');
-        }
-        number /= 5;
+/// Converts a decimal number to SNAFU.
+#[must_use]
+pub fn unconvert(mut number: i64) -> String {
+    if number == 0 {
+        return "0".to_string();
     }
-    out.reverse();
 
-    // SAFETY: this can only be valid unicode
-    unsafe { String::from_utf8_unchecked(out) }
+    let mut out = Vec::new();
+    while number != 0 {
+        // digits above 2 borrow one from the next place up, going negative here instead
+        let (digit, carry) = match number.rem_euclid(5) {
+            0 => ('0', 0),
+            1 => ('1', 0),
+            2 => ('2', 0),
+            3 => ('=', 1),
+            _ => ('-', 1),
+        };
+        out.push(digit);
+        number = number.div_euclid(5) + carry;
+    }
+    out.iter().rev().collect()
 }
 
-fn solve(filename: &str) -> aoc::Result<(i64, String)> {
-    let reader = aoc::file(filename)?;
-
-    let sum = reader.lines().map(|v| convert(&v.unwrap())).sum();
-    let snafu_sum = unconvert(sum);
+pub struct Day25;
+
+/// Day 25 only has one puzzle, so part 2 reports the decimal sum behind the SNAFU answer.
+impl Solution for Day25 {
+    const DAY: u32 = 25;
+
+    type Input = Vec<i64>;
+    type Params = ();
+    type Answer1 = String;
+    type Answer2 = i64;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        // kept as it goes, so the parts can add everything up without overflowing
+        let mut total = 0i64;
+        NumberedLines::new(Self::DAY, reader)
+            .map(|line| {
+                let line = line?;
+                match try_convert(&line.text) {
+                    Some(number) if !line.text.is_empty() => {
+                        total = total.checked_add(number).ok_or_else(|| {
+                            line.error("a SNAFU number that keeps the total within 64 bits")
+                        })?;
+                        Ok(number)
+                    }
+                    _ => Err(line
+                        .error("a SNAFU number made of `=`, `-`, 0, 1 and 2 that fits in 64 bits")
+                        .into()),
+                }
+            })
+            .collect()
+    }
 
-    assert_eq!(convert(&snafu_sum), sum);
+    fn part1(numbers: &Self::Input, _params: &()) -> String {
+        unconvert(numbers.iter().sum())
+    }
 
-    Ok((sum, snafu_sum))
+    fn part2(numbers: &Self::Input, _params: &()) -> i64 {
+        numbers.iter().sum()
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1_pre, part1) = solve("inputs/day25")?;
+/// `size` fuel requirements of up to ten trillion.
+impl Generate for Day25 {
+    const SIZE: usize = 120;
 
-    println!("{}", part1_pre);
-    println!("{}", part1);
-
-    Ok(())
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        join_lines((0..size.max(1)).map(|_| unconvert(rng.gen_range(1..=10_i64.pow(13)))))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{convert, unconvert, solve};
+    use super::{convert, try_convert, unconvert, Day25};
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
+    use proptest::prelude::*;
 
     #[test]
     fn sample() {
-        let (part1_pre, part1) = solve("inputs-sample/day25").unwrap();
+        let (part1, part1_pre) = solve_file::<Day25>("inputs-sample/day25").unwrap();
 
         assert_eq!(part1_pre, 4890);
         assert_eq!(part1, "2=-1=0");
@@ -109,4 +146,43 @@ mod test {
         assert_eq!(unconvert(24),"10-");
         assert_eq!(convert("10-"), 24);
     }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(try_convert("1=3"), None);
+        // 5^28 is out of range
+        assert_eq!(try_convert(&format!("1{}", "0".repeat(28))), None);
+
+        let big = unconvert(i64::MAX);
+        for (input, line) in [
+            ("1=\n\n", 2),
+            ("1=\n12\n1x\n", 3),
+            (&format!("{}\n-\n1\n1\n", big), 4),
+        ] {
+            match solve::<Day25, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn negative() {
+        assert_eq!(unconvert(0), "0");
+        assert_eq!(unconvert(-1), "-");
+        assert_eq!(unconvert(-3), "-2");
+        assert_eq!(
+            solve::<Day25, _>("-\n=\n".as_bytes()).unwrap(),
+            ("-2".to_string(), -3)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(n in any::<i64>()) {
+            let snafu = unconvert(n);
+            prop_assert!(snafu.bytes().all(|b| b"=-012".contains(&b)), "{}", snafu);
+            prop_assert_eq!(convert(&snafu), n);
+        }
+    }
 }
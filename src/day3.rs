@@ -1,6 +1,10 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+};
+use rand::{seq::SliceRandom, Rng};
 use std::collections::{BTreeMap, BTreeSet};
 use std::io::BufRead;
 
@@ -20,85 +24,175 @@ impl Item {
         } else if self.0 >= b'A' && self.0 <= b'Z' {
             u32::from(self.0 - b'A' + 27)
         } else {
-            unreachable!()
+            unreachable!("Rucksack::parse only accepts letters")
         }
     }
 }
 #[derive(Debug)]
-struct Rucksack(BTreeSet<Item>, BTreeSet<Item>);
+pub struct Rucksack(BTreeSet<Item>, BTreeSet<Item>);
 
 impl Rucksack {
-    fn parse(s: &str) -> Self {
+    /// Parses a rucksack, which needs exactly one item in both compartments.
+    fn parse(s: &str) -> Option<Self> {
         let bytes = s.as_bytes();
         let mid = bytes.len() / 2;
-        assert_eq!(mid * 2, bytes.len());
+        if mid * 2 != bytes.len() || !bytes.iter().all(u8::is_ascii_alphabetic) {
+            return None;
+        }
 
-        Rucksack(
+        let sack = Rucksack(
             bytes[0..mid].iter().copied().map(Item).collect(),
             bytes[mid..].iter().copied().map(Item).collect(),
-        )
+        );
+        sack.in_both().map(|_| sack)
     }
 
     fn all_items(&self) -> impl Iterator<Item = Item> + '_ {
         self.0.union(&self.1).copied()
     }
 
-    fn in_both(&self) -> Item {
+    /// The one item in both compartments, or `None` if there isn't exactly one.
+    fn in_both(&self) -> Option<Item> {
         let mut intersect = self.0.intersection(&self.1);
-        let result = intersect.next().unwrap();
-        assert!(intersect.next().is_none());
-        *result
+        let result = intersect.next()?;
+        intersect.next().is_none().then_some(*result)
+    }
+}
+
+/// The one item all three rucksacks in a group carry, or `None` if there isn't exactly one.
+fn badge(group: &[Rucksack]) -> Option<Item> {
+    let mut item_counts: BTreeMap<Item, usize> = BTreeMap::new();
+    for sack in group {
+        for item in sack.all_items() {
+            *item_counts.entry(item).or_default() += 1;
+        }
     }
+    item_counts.retain(|_, v| *v == group.len());
+
+    let mut common = item_counts.into_keys();
+    let result = common.next()?;
+    common.next().is_none().then_some(result)
 }
-fn solve(filename: &str) -> aoc::Result<(u32, u32)> {
-    let reader = aoc::file(filename)?;
 
-    let sacks: Vec<_> = reader
-        .lines()
-        .map(|line| Rucksack::parse(&line.unwrap()))
-        .collect();
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u32 = 3;
+
+    type Input = Vec<Rucksack>;
+    type Params = ();
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let mut lines = NumberedLines::new(Self::DAY, reader);
+        let mut sacks = Vec::new();
+        for line in lines.by_ref() {
+            let line = line?;
+            let sack = Rucksack::parse(&line.text).ok_or_else(|| {
+                line.error("an even number of letters, with exactly one item in both halves")
+            })?;
+            sacks.push(sack);
+
+            // each group of three is checked as it's completed
+            if sacks.len() % 3 == 0 && badge(&sacks[sacks.len() - 3..]).is_none() {
+                return Err(line
+                    .error("a rucksack sharing exactly one item with the two before it")
+                    .into());
+            }
+        }
+        if sacks.len() % 3 != 0 {
+            return Err(lines
+                .end_of_input("the rest of a group of three rucksacks")
+                .into());
+        }
+        Ok(sacks)
+    }
 
-    Ok((
+    fn part1(sacks: &Self::Input, _params: &()) -> u32 {
         sacks
             .iter()
-            .map(|sack| sack.in_both().priority())
-            .sum::<u32>(),
+            .map(|sack| sack.in_both().expect("checked when parsing").priority())
+            .sum()
+    }
+
+    fn part2(sacks: &Self::Input, _params: &()) -> u32 {
         sacks
             .chunks(3)
-            .map(|group| {
-                let mut item_counts: BTreeMap<Item, u32> = BTreeMap::new();
-                for sack in group {
-                    for item in sack.all_items() {
-                        *item_counts.entry(item).or_default() += 1;
-                    }
-                }
-                item_counts.retain(|_, v| *v == 3);
-                assert_eq!(item_counts.len(), 1);
-
-                item_counts.keys().next().unwrap().priority()
-            })
-            .sum::<u32>(),
-    ))
+            .map(|group| badge(group).expect("checked when parsing").priority())
+            .sum()
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day3")?;
-
-    println!("{}", part1);
-    println!("{}", part2);
-
-    Ok(())
+/// At least `size` rucksacks, in groups of three.
+///
+/// Each group shares exactly one badge and each compartment exactly one item with its other half,
+/// which is done by giving every elf in a group their own third of the alphabet to pack from.
+impl Generate for Day3 {
+    const SIZE: usize = 300;
+
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let mut items: Vec<u8> = (b'a'..=b'z').chain(b'A'..=b'Z').collect();
+        let mut lines = Vec::new();
+        for _ in 0..size.div_ceil(3).max(1) {
+            items.shuffle(rng);
+            let (badge, rest) = items.split_first().expect("there are 52 items");
+            for own in rest.chunks(17) {
+                // the first half packs from `left`, the second from `right`
+                let (left, right) = own.split_at(8);
+                // either one of their own or the badge, which is then in both halves
+                let shared = own.get(rng.gen_range(0..=own.len())).unwrap_or(badge);
+                let len = rng.gen_range(4..=16);
+
+                let mut first = vec![*shared, *badge];
+                first.extend((first.len()..len).map(|_| left[rng.gen_range(0..left.len())]));
+                let mut second = vec![*shared];
+                second.extend((second.len()..len).map(|_| right[rng.gen_range(0..right.len())]));
+                first.shuffle(rng);
+                second.shuffle(rng);
+
+                first.extend(second);
+                lines.push(String::from_utf8(first).expect("items are letters"));
+            }
+        }
+        join_lines(lines)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day3;
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day3").unwrap();
+        let (part1, part2) = solve_file::<Day3>("inputs-sample/day3").unwrap();
 
         assert_eq!(part1, 157);
         assert_eq!(part2, 70);
     }
+
+    #[test]
+    fn malformed() {
+        for (input, line) in [
+            ("abc", 1),
+            ("ab1c", 1),
+            // no item in both halves, then two
+            ("abcd", 1),
+            ("aa\nabab", 2),
+            // no badge, then several
+            ("aa\nbb\ncc", 3),
+            ("abac\nbaca\ncbca", 3),
+            // a group cut short
+            ("aa\nbb", 3),
+        ] {
+            match solve::<Day3, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line, "{}", input),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
 }
@@ -1,67 +1,93 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    intspan::IntSpan,
+    solution::Solution,
+};
+use rand::Rng;
 use regex::Regex;
 use std::io::BufRead;
 
-struct Span(u32, u32);
+pub struct Day4;
 
-impl Span {
-    fn fully_contains(&self, other: &Self) -> bool {
-        other.0 >= self.0 && other.1 <= self.1
-    }
+impl Solution for Day4 {
+    const DAY: u32 = 4;
 
-    fn overlaps(&self, other: &Self) -> bool {
-        other.0 >= self.0 && other.0 <= self.1 || other.1 >= self.0 && other.1 <= self.1
-    }
-}
+    type Input = Vec<(IntSpan, IntSpan)>;
+    type Params = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-fn solve(filename: &str) -> aoc::Result<(usize, usize)> {
-    let reader = aoc::file(filename)?;
-    let parse_regex = Regex::new(r"^(\d+)-(\d+),(\d+)-(\d+)$").unwrap();
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let parse_regex = Regex::new(r"^(\d+)-(\d+),(\d+)-(\d+)$").unwrap();
 
-    let spans: Vec<_> = reader
-        .lines()
-        .map(|d| {
-            let line = d.unwrap();
-            let captures = parse_regex.captures(&line).unwrap();
-            let span1 = Span(captures[1].parse().unwrap(), captures[2].parse().unwrap());
-            let span2 = Span(captures[3].parse().unwrap(), captures[4].parse().unwrap());
+        NumberedLines::new(Self::DAY, reader)
+            .map(|line| {
+                let line = line?;
+                let spans = parse_regex.captures(&line.text).and_then(|captures| {
+                    let span = |i: usize| {
+                        let (min, max) = (captures[i].parse().ok()?, captures[i + 1].parse().ok()?);
+                        (min <= max).then(|| IntSpan::with_range(min, max))
+                    };
+                    Some((span(1)?, span(3)?))
+                });
 
-            (span1, span2)
-        })
-        .collect();
+                spans.ok_or_else(|| line.error("`<a>-<b>,<c>-<d>`").into())
+            })
+            .collect()
+    }
 
-    Ok((
+    fn part1(spans: &Self::Input, _params: &()) -> usize {
         spans
             .iter()
-            .filter(|(span1, span2)| span1.fully_contains(span2) || span2.fully_contains(span1))
-            .count(),
+            .filter(|(span1, span2)| span1.is_superset(span2) || span2.is_superset(span1))
+            .count()
+    }
+
+    fn part2(spans: &Self::Input, _params: &()) -> usize {
         spans
             .iter()
-            .filter(|(span1, span2)| span1.overlaps(span2) || span2.overlaps(span1))
-            .count(),
-    ))
+            .filter(|(span1, span2)| span1.intersects(span2))
+            .count()
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day4")?;
+/// `size` pairs of sections between 1 and 99.
+impl Generate for Day4 {
+    const SIZE: usize = 1000;
 
-    println!("{}", part1);
-    println!("{}", part2);
-
-    Ok(())
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let mut span = || {
+            let min = rng.gen_range(1..=99);
+            (min, rng.gen_range(min..=99))
+        };
+        join_lines((0..size.max(1)).map(|_| {
+            let (a, b) = span();
+            let (c, d) = span();
+            format!("{}-{},{}-{}", a, b, c, d)
+        }))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day4;
+    use crate::solution::{solve, solve_file};
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day4").unwrap();
+        let (part1, part2) = solve_file::<Day4>("inputs-sample/day4").unwrap();
 
         assert_eq!(part1, 2);
         assert_eq!(part2, 4);
     }
+
+    #[test]
+    fn touching_spans() {
+        let (part1, part2) = solve::<Day4, _>("1-3,3-5\n1-2,3-4\n".as_bytes()).unwrap();
+
+        assert_eq!(part1, 0);
+        assert_eq!(part2, 1);
+    }
 }
@@ -1,83 +1,218 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+};
+use rand::Rng;
 use regex::Regex;
 use std::{collections::VecDeque, io::BufRead};
 
-fn solve(filename: &str) -> aoc::Result<(String, String)> {
-    let reader = aoc::file(filename)?;
-    let parse_regex = Regex::new(r"(?:\[(.)\]|   )(?: |$)").unwrap();
-    let move_regex = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
-
-    let mut lines = reader.lines();
-    let mut has_capture = true;
-    let mut stacks: Vec<VecDeque<char>> = Vec::new();
-
-    while has_capture {
-        has_capture = false;
-        let line = lines.next().unwrap()?;
-        for (i, cap) in parse_regex.captures_iter(&line).enumerate() {
-            has_capture = true;
-            if i >= stacks.len() {
-                stacks.push(VecDeque::new());
-            }
-            if let Some(val) = cap.get(1) {
-                stacks[i].push_front(val.as_str().chars().next().unwrap());
-            }
-        }
+#[derive(Debug, Clone, Copy)]
+pub struct Move {
+    count: usize,
+    src: usize,
+    dest: usize,
+}
+
+#[derive(Debug)]
+pub struct Crates {
+    stacks: Vec<VecDeque<char>>,
+    moves: Vec<Move>,
+}
+
+impl Crates {
+    /// The crate on top of each stack, skipping any left empty.
+    fn tops(stacks: &[VecDeque<char>]) -> String {
+        stacks.iter().filter_map(VecDeque::back).collect()
     }
+}
+
+pub struct Day5;
 
-    assert_eq!(lines.next().unwrap()?, "");
+impl Solution for Day5 {
+    const DAY: u32 = 5;
 
-    let mut stacks2 = stacks.clone();
+    type Input = Crates;
+    type Params = ();
+    type Answer1 = String;
+    type Answer2 = String;
 
-    for line in lines {
-        let line = line.unwrap();
-        let cap = move_regex
-            .captures(&line)
-            .expect("Failed to match move line");
-        let n = cap[1].parse::<usize>().unwrap();
-        let src = cap[2].parse::<usize>().unwrap() - 1;
-        let dest = cap[3].parse::<usize>().unwrap() - 1;
-        assert!(src < stacks.len());
-        assert!(dest < stacks.len());
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let parse_regex = Regex::new(r"(?:\[(.)\]|   )(?: |$)").unwrap();
+        let move_regex = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
 
-        let mut tmp: Vec<char> = Vec::new();
-        for _ in 0..n {
-            let item = stacks[src].pop_back().unwrap();
-            stacks[dest].push_back(item);
+        let mut lines = NumberedLines::new(Self::DAY, reader);
+        let mut has_capture = true;
+        let mut stacks: Vec<VecDeque<char>> = Vec::new();
 
-            tmp.push(stacks2[src].pop_back().unwrap());
+        while has_capture {
+            has_capture = false;
+            let line = lines.expect_next("a row of crates or the stack numbers")?;
+            for (i, cap) in parse_regex.captures_iter(&line.text).enumerate() {
+                has_capture = true;
+                if i >= stacks.len() {
+                    stacks.push(VecDeque::new());
+                }
+                if let Some(val) = cap.get(1) {
+                    stacks[i].push_front(val.as_str().chars().next().unwrap());
+                }
+            }
         }
-        for item in tmp.into_iter().rev() {
-            stacks2[dest].push_back(item);
+
+        let line = lines.expect_next("a blank line after the stack numbers")?;
+        if !line.text.is_empty() {
+            return Err(line.error("a blank line after the stack numbers").into());
         }
+
+        // stack heights as the moves play out, so a move can't take crates that aren't there
+        let mut heights: Vec<usize> = stacks.iter().map(VecDeque::len).collect();
+        let mut moves = Vec::new();
+        for line in lines {
+            let line = line?;
+            let expected =
+                || line.error(format!("`move <n> from <1-{0}> to <1-{0}>`", stacks.len()));
+            let cap = move_regex.captures(&line.text).ok_or_else(expected)?;
+            let stack = |n: &str| {
+                n.parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .filter(|&n| n < stacks.len())
+            };
+            let count = cap[1].parse::<usize>().map_err(|_| expected())?;
+            let src = stack(&cap[2]).ok_or_else(expected)?;
+            let dest = stack(&cap[3]).ok_or_else(expected)?;
+            if count > heights[src] {
+                return Err(line
+                    .error(format!(
+                        "a move of at most {} crates from stack {}",
+                        heights[src],
+                        src + 1
+                    ))
+                    .into());
+            }
+            heights[src] -= count;
+            heights[dest] += count;
+
+            moves.push(Move { count, src, dest });
+        }
+
+        Ok(Crates { stacks, moves })
     }
 
-    Ok((
-        stacks.iter().map(|v| v.back().unwrap()).collect(),
-        stacks2.iter().map(|v| v.back().unwrap()).collect(),
-    ))
+    fn part1(crates: &Self::Input, _params: &()) -> String {
+        let mut stacks = crates.stacks.clone();
+        for &Move { count, src, dest } in &crates.moves {
+            for _ in 0..count {
+                let item = stacks[src].pop_back().unwrap();
+                stacks[dest].push_back(item);
+            }
+        }
+
+        Crates::tops(&stacks)
+    }
+
+    fn part2(crates: &Self::Input, _params: &()) -> String {
+        let mut stacks = crates.stacks.clone();
+        for &Move { count, src, dest } in &crates.moves {
+            let mut tmp: Vec<char> = Vec::new();
+            for _ in 0..count {
+                tmp.push(stacks[src].pop_back().unwrap());
+            }
+            for item in tmp.into_iter().rev() {
+                stacks[dest].push_back(item);
+            }
+        }
+
+        Crates::tops(&stacks)
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day5")?;
+/// Up to nine stacks, then `size` moves.
+///
+/// No move ever takes the last crate off a stack, so every stack has a crate on top at the end.
+impl Generate for Day5 {
+    const SIZE: usize = 500;
+
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let stacks: Vec<Vec<char>> = (0..rng.gen_range(3..=9))
+            .map(|_| {
+                (0..rng.gen_range(2..=8))
+                    .map(|_| char::from(rng.gen_range(b'A'..=b'Z')))
+                    .collect()
+            })
+            .collect();
 
-    println!("{}", part1);
-    println!("{}", part2);
+        let tallest = stacks.iter().map(Vec::len).max().unwrap_or(0);
+        let mut lines: Vec<String> = (0..tallest)
+            .rev()
+            .map(|row| {
+                let crates: Vec<String> = stacks
+                    .iter()
+                    .map(|stack| {
+                        stack
+                            .get(row)
+                            .map_or_else(|| "   ".to_string(), |c| format!("[{}]", c))
+                    })
+                    .collect();
+                crates.join(" ")
+            })
+            .collect();
+        let numbers: Vec<String> = (1..=stacks.len()).map(|n| format!(" {} ", n)).collect();
+        lines.push(numbers.join(" "));
+        lines.push(String::new());
 
-    Ok(())
+        // with at least two crates a stack, some stack always has one to spare
+        let mut heights: Vec<usize> = stacks.iter().map(Vec::len).collect();
+        for _ in 0..size {
+            let spare: Vec<usize> = (0..heights.len()).filter(|&i| heights[i] > 1).collect();
+            let src = spare[rng.gen_range(0..spare.len())];
+            let dest = (src + rng.gen_range(1..heights.len())) % heights.len();
+            let count = rng.gen_range(1..heights[src]);
+            heights[src] -= count;
+            heights[dest] += count;
+            lines.push(format!("move {} from {} to {}", count, src + 1, dest + 1));
+        }
+        join_lines(lines)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day5;
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day5").unwrap();
+        let (part1, part2) = solve_file::<Day5>("inputs-sample/day5").unwrap();
 
         assert_eq!(part1, "CMZ");
         assert_eq!(part2, "MCD");
     }
+
+    #[test]
+    fn malformed() {
+        let stacks = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n";
+        for (moves, line) in [
+            ("move 1 from 2 to 4\n", 6),
+            ("move 3 from 1 to 3\n", 6),
+            ("move 2 from 1 to 3\nmove 4 from 3 to 2\n", 7),
+        ] {
+            let input = format!("{}{}", stacks, moves);
+            match solve::<Day5, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line, "{}", moves),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+
+        // emptying a stack is fine; it just has no crate on top
+        let input = format!("{}move 1 from 3 to 1\n", stacks);
+        assert_eq!(
+            solve::<Day5, _>(input.as_bytes()).unwrap(),
+            ("PD".to_string(), "PD".to_string())
+        );
+    }
 }
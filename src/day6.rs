@@ -1,6 +1,10 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    generate::{Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+};
+use rand::{seq::SliceRandom, Rng};
 use std::io::BufRead;
 
 fn check_counts(counts: &[u8; 26]) -> bool {
@@ -12,7 +16,9 @@ fn check_counts(counts: &[u8; 26]) -> bool {
 
     true
 }
-fn find_marker(char_buf: &str, n_unique: usize) -> Option<usize> {
+/// Returns how many characters have been read once the last `n_unique` were all different.
+#[must_use]
+pub fn find_marker(char_buf: &str, n_unique: usize) -> Option<usize> {
     let mut counts = [0u8; 26];
     let buf = char_buf.as_bytes();
 
@@ -40,19 +46,78 @@ fn find_marker(char_buf: &str, n_unique: usize) -> Option<usize> {
     None
 }
 
-fn main() -> aoc::Result<()> {
-    let reader = aoc::file("inputs/day6")?;
+pub struct Day6;
 
-    let data = reader.lines().next().unwrap().unwrap();
+impl Solution for Day6 {
+    const DAY: u32 = 6;
 
-    println!("{}", find_marker(&data, 4).unwrap());
-    println!("{}", find_marker(&data, 14).unwrap());
-    Ok(())
+    type Input = String;
+    type Params = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let expected = "a line of lowercase letters";
+        let line = NumberedLines::new(Self::DAY, reader).expect_next(expected)?;
+        if !line.text.bytes().all(|b| b.is_ascii_lowercase()) {
+            return Err(line.error(expected).into());
+        }
+        // fourteen different letters in a row hold four different ones too
+        if find_marker(&line.text, 14).is_none() {
+            return Err(line
+                .error("a stream with fourteen different letters in a row")
+                .into());
+        }
+
+        Ok(line.text)
+    }
+
+    fn part1(data: &Self::Input, _params: &()) -> usize {
+        find_marker(data, 4).expect("checked when parsing")
+    }
+
+    fn part2(data: &Self::Input, _params: &()) -> usize {
+        find_marker(data, 14).expect("checked when parsing")
+    }
+}
+
+/// A stream of `size` letters, with fourteen different ones in a row somewhere after a stretch
+/// that only uses three.
+impl Generate for Day6 {
+    const SIZE: usize = 4096;
+
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let len = size.max(14);
+        let mut letters: Vec<u8> = (b'a'..=b'z').collect();
+        letters.shuffle(rng);
+
+        let mut stream: Vec<u8> = (0..rng.gen_range(0..=len - 14))
+            .map(|_| letters[rng.gen_range(0..3)])
+            .collect();
+        stream.extend(&letters[..14]);
+        stream.extend((stream.len()..len).map(|_| rng.gen_range(b'a'..=b'z')));
+
+        let mut text = String::from_utf8(stream).expect("only lowercase letters");
+        text.push('\n');
+        text
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::find_marker;
+    use super::{find_marker, Day6};
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
+
+    #[test]
+    fn sample() {
+        let (part1, part2) = solve_file::<Day6>("inputs-sample/day6").unwrap();
+
+        assert_eq!(part1, 7);
+        assert_eq!(part2, 19);
+    }
 
     #[test]
     fn part1() {
@@ -83,4 +148,14 @@ mod test {
             Some(26)
         );
     }
+
+    #[test]
+    fn malformed() {
+        for input in ["", "abC", "aaaa", "abcdefghijklmabcdefghijklm"] {
+            match solve::<Day6, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, 1, "{:?}", input),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
 }
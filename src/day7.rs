@@ -1,12 +1,16 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    filesystem::{Filesystem, NodeId},
+    generate::{join_lines, Generate, Random},
+    input::{Line, NumberedLines},
+    solution::Solution,
+};
 use lazy_static::lazy_static;
+use rand::{seq::SliceRandom, Rng};
 use regex::Regex;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::HashSet;
 use std::io::BufRead;
-use std::rc::Rc;
 
 lazy_static! {
     static ref CMD_CHDIR: Regex = Regex::new(r"^\$ cd (.+)$").unwrap();
@@ -14,111 +18,558 @@ lazy_static! {
     static ref LS_FILE: Regex = Regex::new(r"^(\d+) (.+)$").unwrap();
 }
 
-type Directory = Rc<RefCell<HashMap<String, DirectoryEntry>>>;
+/// Replays a transcript a line at a time, filling in the filesystem as `ls` reveals it.
+struct Shell {
+    fs: Filesystem,
+    cwd: NodeId,
+    in_ls: bool,
+}
+
+impl Shell {
+    fn new() -> Self {
+        Self {
+            fs: Filesystem::new(),
+            cwd: Filesystem::ROOT,
+            in_ls: false,
+        }
+    }
 
-#[derive(Debug)]
-enum DirectoryEntry {
-    File(usize),
-    Directory(Directory),
+    fn execute(&mut self, line: &Line) -> Result<()> {
+        let text = &line.text;
+        let conflict = || line.error("a listing that doesn't turn a file into a directory or back");
+        if let Some(x) = CMD_CHDIR.captures(text) {
+            self.in_ls = false;
+            self.cwd = self
+                .fs
+                .resolve(self.cwd, &x[1])
+                .filter(|&dir| self.fs.is_directory(dir))
+                .ok_or_else(|| {
+                    line.error("`$ cd` to a directory listed by `$ ls`, without leaving the root")
+                })?;
+        } else if text == "$ ls" {
+            self.in_ls = true;
+        } else if text.starts_with('$') {
+            return Err(line.error("`$ cd <path>` or `$ ls`").into());
+        } else if !self.in_ls {
+            return Err(line.error("`$ ls` before any listing").into());
+        } else if let Some(x) = LS_DIR.captures(text) {
+            // listing a directory again finds the one already known, rather than adding another
+            self.fs
+                .add_directory(self.cwd, &x[1])
+                .ok_or_else(conflict)?;
+        } else if let Some((size, name)) = LS_FILE
+            .captures(text)
+            .and_then(|x| Some((x[1].parse::<usize>().ok()?, x[2].to_string())))
+        {
+            if self.fs.used().checked_add(size).is_none() {
+                return Err(line
+                    .error("file sizes that add up to at most `usize::MAX`")
+                    .into());
+            }
+            self.fs
+                .add_file(self.cwd, &name, size)
+                .ok_or_else(conflict)?;
+        } else {
+            return Err(line.error("`dir <name>` or `<size> <name>`").into());
+        }
+        Ok(())
+    }
 }
 
-fn directory_size(dir_cell: &Directory) -> usize {
-    let dir = dir_cell.borrow();
-    dir.values()
-        .map(|v| match v {
-            DirectoryEntry::File(size) => *size,
-            DirectoryEntry::Directory(subdir) => directory_size(subdir),
-        })
-        .sum()
+/// Size of the disk and how much free space the update needs; the worked example and the real
+/// puzzle both use the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub disk_size: usize,
+    pub needed_space: usize,
 }
 
-fn solve(filename: &str) -> aoc::Result<(usize, usize)> {
-    let reader = aoc::file(filename)?;
-
-    let directory_root: Directory = Directory::default();
-    let mut directory_tree: Vec<Directory> = vec![directory_root.clone()];
-    let mut all_directories: Vec<Directory> = vec![directory_root.clone()];
-    let mut cwd: Directory = directory_root.clone();
-
-    let mut in_ls = false;
-
-    for line in reader.lines() {
-        let line = &line?;
-        if let Some(x) = CMD_CHDIR.captures(line) {
-            in_ls = false;
-            if &x[1] == "/" {
-                directory_tree = vec![directory_root.clone()];
-            } else if x[1].contains('/') {
-                unimplemented!("multi chdir");
-            } else if &x[1] == ".." {
-                directory_tree.pop();
-            } else if let Some(DirectoryEntry::Directory(next_dir)) = cwd.borrow().get(&x[1]) {
-                directory_tree.push(next_dir.clone());
-            } else {
-                panic!("missing direcrory {}", &x[1]);
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            disk_size: 70_000_000,
+            needed_space: 30_000_000,
+        }
+    }
+}
+
+impl Params {
+    /// How much has to be deleted to make room, when `used` of the disk is taken.
+    #[must_use]
+    pub fn to_free(&self, used: usize) -> usize {
+        self.needed_space
+            .saturating_sub(self.disk_size.saturating_sub(used))
+    }
+}
+
+/// Most choices the deletion planner tries before settling for the best plan it has found.
+pub const SEARCH_LIMIT: usize = 10_000_000;
+
+/// Files and directories to delete together, none of them inside another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletionPlan {
+    pub nodes: Vec<NodeId>,
+    /// Space that had to be freed.
+    pub target: usize,
+    /// Space the plan frees, at least `target`.
+    pub freed: usize,
+    /// The smallest lone directory that frees enough, which is what part 2 picks.
+    pub smallest_directory: Option<NodeId>,
+    /// Whether every combination was weighed, rather than giving up at [`SEARCH_LIMIT`].
+    pub exhaustive: bool,
+}
+
+impl DeletionPlan {
+    /// What the plan deletes and why, one line each.
+    #[must_use]
+    pub fn explain(&self, fs: &Filesystem) -> String {
+        let mut lines = vec![format!("to free {}, delete:", self.target)];
+        for &id in &self.nodes {
+            lines.push(format!("  {}\t{}", fs.size(id), fs.path(id)));
+        }
+        lines.push(format!(
+            "which frees {}, {} more than needed",
+            self.freed,
+            self.freed - self.target
+        ));
+        if let Some(dir) = self.smallest_directory.filter(|&dir| self.nodes != [dir]) {
+            let size = fs.size(dir);
+            lines.push(format!(
+                "deleting just {} would free {}, {} more than needed",
+                fs.path(dir),
+                size,
+                size - self.target
+            ));
+        }
+        if !self.exhaustive {
+            lines.push(format!(
+                "gave up after {} choices, so a closer plan may exist",
+                SEARCH_LIMIT
+            ));
+        }
+        join_lines(lines)
+    }
+}
+
+/// A first guess for the planner to beat: the largest candidates that fit under `target`, then
+/// the smallest that takes it over. `ends` says where the candidates inside each one end.
+fn greedy_plan(sizes: &[usize], ends: &[usize], target: usize) -> Option<(Vec<usize>, usize)> {
+    let nested = |a: usize, b: usize| (a < b && b < ends[a]) || (b < a && a < ends[b]);
+    let mut by_size: Vec<usize> = (0..sizes.len()).collect();
+    by_size.sort_by_key(|&i| Reverse(sizes[i]));
+
+    let mut chosen: Vec<usize> = Vec::new();
+    let mut freed = 0;
+    for &i in &by_size {
+        if freed + sizes[i] <= target && !chosen.iter().any(|&c| nested(c, i)) {
+            chosen.push(i);
+            freed += sizes[i];
+        }
+    }
+    if freed < target {
+        let last = *by_size
+            .iter()
+            .rev()
+            .find(|&&i| freed + sizes[i] >= target && !chosen.iter().any(|&c| nested(c, i)))?;
+        chosen.push(last);
+        freed += sizes[last];
+    }
+    Some((chosen, freed))
+}
+
+/// Picks files and directories, none inside another, that free at least `target` while deleting
+/// as little extra as possible; only directories unless `files`. `None` if even deleting
+/// everything below the root isn't enough.
+///
+/// Every candidate is either deleted, which rules out everything inside it, or kept, a branch and
+/// bound search that drops any choice which can no longer reach `target` or beat the best plan so
+/// far.
+#[must_use]
+pub fn plan_deletion(fs: &Filesystem, target: usize, files: bool) -> Option<DeletionPlan> {
+    // the candidates in walk order, so everything inside one comes straight after it
+    let candidates: Vec<(NodeId, usize)> = fs
+        .walk(Filesystem::ROOT)
+        .skip(1)
+        .filter(|&(id, _)| (files || fs.is_directory(id)) && fs.size(id) > 0)
+        .collect();
+    let n = candidates.len();
+    let sizes: Vec<usize> = candidates.iter().map(|&(id, _)| fs.size(id)).collect();
+
+    // where the candidates inside each one end
+    let mut ends = vec![n; n];
+    let mut open: Vec<usize> = Vec::new();
+    for (i, &(_, depth)) in candidates.iter().enumerate() {
+        while open.last().is_some_and(|&o| candidates[o].1 >= depth) {
+            ends[open.pop().expect("just looked at it")] = i;
+        }
+        open.push(i);
+    }
+    // the most that can still be freed from each candidate onwards
+    let mut reachable = vec![0; n + 1];
+    for i in (0..n).rev() {
+        reachable[i] = sizes[i] + reachable[ends[i]];
+    }
+    if reachable[0] < target {
+        return None;
+    }
+
+    let smallest_directory = fs
+        .directories()
+        .skip(1)
+        .filter(|&dir| fs.size(dir) >= target)
+        .min_by_key(|&dir| fs.size(dir));
+    let (mut best, mut best_freed) = if target == 0 {
+        (Vec::new(), 0)
+    } else {
+        greedy_plan(&sizes, &ends, target).unwrap_or_else(|| {
+            // everything at the top level always does
+            let mut top = Vec::new();
+            let mut i = 0;
+            while i < n {
+                top.push(i);
+                i = ends[i];
+            }
+            (top, reachable[0])
+        })
+    };
+
+    // next candidate, space freed so far, how many were chosen before, and what this choice adds
+    let mut stack = vec![(0, 0, 0, None)];
+    let mut chosen: Vec<usize> = Vec::new();
+    let mut tried = 0;
+    while best_freed > target {
+        let Some((i, freed, before, taken)) = stack.pop() else {
+            break;
+        };
+        tried += 1;
+        if tried > SEARCH_LIMIT {
+            break;
+        }
+        chosen.truncate(before);
+        chosen.extend(taken);
+
+        if freed >= target {
+            if freed < best_freed {
+                best.clone_from(&chosen);
+                best_freed = freed;
+            }
+        } else if i < n && freed + reachable[i] >= target {
+            stack.push((i + 1, freed, chosen.len(), None));
+            if freed + sizes[i] < best_freed {
+                stack.push((ends[i], freed + sizes[i], chosen.len(), Some(i)));
             }
-            cwd = directory_tree
-                .last()
-                .expect("somehow lost root directory")
-                .clone();
-        } else if line == "$ ls" {
-            in_ls = true;
-        } else if line.starts_with('$') {
-            unimplemented!("invalid command {}", line);
-        } else if !in_ls {
-            unimplemented!("directory data outside ls");
-        } else if let Some(x) = LS_DIR.captures(line) {
-            let mut mut_cwd = cwd.borrow_mut();
-            let new_dir: Directory = Directory::default();
-            all_directories.push(new_dir.clone());
-            mut_cwd.insert(x[1].to_string(), DirectoryEntry::Directory(new_dir));
-        } else if let Some(x) = LS_FILE.captures(line) {
-            let mut mut_cwd = cwd.borrow_mut();
-            mut_cwd.insert(
-                x[2].to_string(),
-                DirectoryEntry::File(x[1].parse::<usize>().unwrap()),
-            );
-        } else {
-            unimplemented!("line {}", line);
         }
     }
 
-    let mut dir_sizes: Vec<usize> = all_directories.iter().map(directory_size).collect();
+    best.sort_unstable();
+    Some(DeletionPlan {
+        nodes: best.iter().map(|&i| candidates[i].0).collect(),
+        target,
+        freed: best_freed,
+        smallest_directory,
+        exhaustive: tried <= SEARCH_LIMIT,
+    })
+}
+
+pub struct Day7;
 
-    let filesystem_size: usize = 70_000_000;
-    let needed_space: usize = 30_000_000;
-    let total_use = dir_sizes[0]; // we need this before we sort.
+impl Solution for Day7 {
+    const DAY: u32 = 7;
 
-    dir_sizes.sort_unstable();
+    type Input = Filesystem;
+    type Params = Params;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    let part1: usize = dir_sizes.iter().filter(|&&size| size <= 100_000).sum();
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let mut shell = Shell::new();
+        for line in NumberedLines::new(Self::DAY, reader) {
+            shell.execute(&line?)?;
+        }
 
-    let free_space = filesystem_size - total_use;
-    let to_free = needed_space - free_space;
+        Ok(shell.fs)
+    }
 
-    let part2: usize = *dir_sizes.iter().find(|&&v| v > to_free).unwrap();
+    fn part1(fs: &Self::Input, _params: &Params) -> usize {
+        fs.directories()
+            .map(|dir| fs.size(dir))
+            .filter(|&size| size <= 100_000)
+            .sum()
+    }
 
-    Ok((part1, part2))
+    /// The smallest directory that frees enough, or the root's size if none does, as deleting
+    /// everything is as close as it gets.
+    fn part2(fs: &Self::Input, params: &Params) -> usize {
+        let used = fs.size(Filesystem::ROOT);
+        let to_free = params.to_free(used);
+
+        fs.directories()
+            .map(|dir| fs.size(dir))
+            .filter(|&v| v >= to_free)
+            .min()
+            .unwrap_or(used)
+    }
+}
+
+/// A lowercase name that isn't in `taken` yet, sometimes with an extension.
+fn fresh_name(rng: &mut Random, taken: &mut HashSet<String>, extension: bool) -> String {
+    loop {
+        let mut name: String = (0..rng.gen_range(1..=8))
+            .map(|_| char::from(rng.gen_range(b'a'..=b'z')))
+            .collect();
+        if extension && rng.gen() {
+            name.push('.');
+            name.push_str(["txt", "dat", "log", "lst", "ext"][rng.gen_range(0..5)]);
+        }
+        if taken.insert(name.clone()) {
+            return name;
+        }
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day7")?;
+/// A transcript exploring `size` directories, each nested in one of the few made just before it
+/// so the tree gets deep.
+///
+/// The files add up to between 45 and 65 million, so part 2 always has something to delete.
+impl Generate for Day7 {
+    const SIZE: usize = 200;
 
-    println!("{}", part1);
-    println!("{}", part2);
+    fn generate(rng: &mut Random, size: usize, _params: &Params) -> String {
+        let size = size.max(1);
+        let mut names = vec![HashSet::new(); size];
+        let mut subdirs = vec![Vec::new(); size];
+        for dir in 1..size {
+            let parent = rng.gen_range(dir.saturating_sub(3)..dir);
+            let name = fresh_name(rng, &mut names[parent], false);
+            subdirs[parent].push((name, dir));
+        }
+
+        // small files everywhere, then one big one to bring the total up to the target
+        let mut files = vec![Vec::new(); size];
+        let count: usize = (0..size).map(|_| rng.gen_range(0..=4)).sum();
+        let largest = (40_000_000 / count.max(1)).clamp(1, 300_000);
+        let mut total = 0;
+        for _ in 0..count {
+            let file_size = rng.gen_range(1..=largest);
+            files[rng.gen_range(0..size)].push(file_size);
+            total += file_size;
+        }
+        files[rng.gen_range(0..size)].push(rng.gen_range(45_000_000..=65_000_000) - total);
+
+        let mut listings: Vec<Vec<String>> = Vec::with_capacity(size);
+        for dir in 0..size {
+            let mut listing: Vec<String> = subdirs[dir]
+                .iter()
+                .map(|(name, _)| format!("dir {}", name))
+                .collect();
+            for &file_size in &files[dir] {
+                let name = fresh_name(rng, &mut names[dir], true);
+                listing.push(format!("{} {}", file_size, name));
+            }
+            listing.shuffle(rng);
+            listings.push(listing);
+        }
 
-    Ok(())
+        // depth first, without recursing, since the tree can be as deep as it is large
+        let mut lines = vec!["$ cd /".to_string(), "$ ls".to_string()];
+        lines.extend(listings[0].iter().cloned());
+        let mut path = vec![(0, 0)];
+        while let Some(&(dir, next)) = path.last() {
+            if let Some((name, subdir)) = subdirs[dir].get(next) {
+                path.last_mut().expect("just looked at it").1 += 1;
+                lines.push(format!("$ cd {}", name));
+                lines.push("$ ls".to_string());
+                lines.extend(listings[*subdir].iter().cloned());
+                path.push((*subdir, 0));
+            } else {
+                path.pop();
+                if !path.is_empty() {
+                    lines.push("$ cd ..".to_string());
+                }
+            }
+        }
+        join_lines(lines)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::{plan_deletion, Day7, Filesystem, NodeId, Params};
+    use crate::{
+        error::Error,
+        generate::{Generate, Random},
+        solution::{solve, solve_file, Solution},
+    };
+    use rand::SeedableRng;
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day7").unwrap();
+        let (part1, part2) = solve_file::<Day7>("inputs-sample/day7").unwrap();
 
         assert_eq!(part1, 95437);
         assert_eq!(part2, 24933642);
     }
+
+    #[test]
+    fn paths() {
+        let sizes = |transcript: &str| -> Vec<usize> {
+            let fs = Day7::parse(transcript.as_bytes()).unwrap();
+            fs.directory_sizes().map(|(_, size)| size).collect()
+        };
+        let listing = "$ cd /\n$ ls\ndir a\n1 f\n$ cd a\n$ ls\ndir b\n10 g\n$ cd b\n$ ls\n100 h\n";
+        assert_eq!(sizes(listing), [111, 110, 100]);
+
+        for extra in [
+            "$ cd /a/b\n$ ls\n100 h\n",
+            "$ cd ../..\n$ cd ./a//b/\n$ ls\n100 h\n",
+            "$ cd /\n$ ls\ndir a\n1 f\n$ cd a/./b/../..\n$ ls\ndir a\n",
+        ] {
+            assert_eq!(sizes(&format!("{}{}", listing, extra)), [111, 110, 100]);
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        for (input, line) in [
+            ("$ cd /\n$ cd ..\n", 2),
+            ("$ cd /\n$ cd a\n", 2),
+            ("$ cd /\n$ ls\ndir a\n$ cd a/b\n", 4),
+            ("$ cd /\n$ ls\n1 a\n$ cd /a\n", 4),
+            ("$ cd /\n$ rm -rf a\n", 2),
+            ("$ cd /\n1 a\n", 2),
+            ("$ ls\nsomething else\n", 2),
+            ("$ ls\ndir a\n1 a\n", 3),
+            ("$ ls\n1 a\ndir a\n", 3),
+            (
+                "$ ls\n18446744073709551615 a\ndir b\n$ cd b\n$ ls\n1 c\n",
+                6,
+            ),
+        ] {
+            match solve::<Day7, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line, "{:?}", input),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn deep() {
+        // thousands of directories deep, which took quadratic time to size up in the old tree
+        let params = Params::default();
+        let input = Day7::generate(&mut Random::seed_from_u64(0), 20_000, &params);
+        let fs = Day7::parse(input.as_bytes()).unwrap();
+        assert_eq!(fs.directories().count(), 20_000);
+        assert!(Day7::part2(&fs, &params) <= fs.size(Filesystem::ROOT));
+    }
+
+    fn sample_fs() -> Filesystem {
+        Day7::parse(std::fs::read("inputs-sample/day7").unwrap().as_slice()).unwrap()
+    }
+
+    #[test]
+    fn params() {
+        let fs = sample_fs();
+        let params = Params {
+            needed_space: 21_700_000,
+            ..Params::default()
+        };
+        assert_eq!(params.to_free(fs.size(Filesystem::ROOT)), 81165);
+        assert_eq!(Day7::part2(&fs, &params), 94853);
+
+        // /a is exactly the size that needs freeing
+        let exact = Params {
+            needed_space: 21_713_688,
+            ..Params::default()
+        };
+        assert_eq!(exact.to_free(fs.size(Filesystem::ROOT)), 94853);
+        assert_eq!(Day7::part2(&fs, &exact), 94853);
+        let a = fs.lookup("/a").unwrap();
+        let plan = plan_deletion(&fs, 94853, false).unwrap();
+        assert_eq!(plan.smallest_directory, Some(a), "agrees with part 2");
+
+        let too_much = Params {
+            needed_space: 80_000_000,
+            ..Params::default()
+        };
+        assert_eq!(Day7::part2(&fs, &too_much), fs.size(Filesystem::ROOT));
+        assert_eq!(Params::default().to_free(1000), 0);
+        assert_eq!(Params::default().to_free(80_000_000), 30_000_000);
+    }
+
+    /// The least that deleting candidates, none inside another, frees while reaching `target`.
+    fn least_freed(fs: &Filesystem, target: usize, files: bool) -> Option<usize> {
+        let candidates: Vec<NodeId> = fs
+            .walk(Filesystem::ROOT)
+            .skip(1)
+            .map(|(id, _)| id)
+            .filter(|&id| files || fs.is_directory(id))
+            .collect();
+        let inside = |mut id: NodeId, dir: NodeId| {
+            while let Some(parent) = fs.parent(id) {
+                if parent == dir {
+                    return true;
+                }
+                id = parent;
+            }
+            false
+        };
+
+        (0..1_u32 << candidates.len())
+            .filter_map(|mask| {
+                let chosen: Vec<NodeId> = (0..candidates.len())
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| candidates[i])
+                    .collect();
+                let nested = chosen.iter().any(|&a| chosen.iter().any(|&b| inside(a, b)));
+                let freed: usize = chosen.iter().map(|&id| fs.size(id)).sum();
+                (!nested && freed >= target).then_some(freed)
+            })
+            .min()
+    }
+
+    #[test]
+    fn planner() {
+        let fs = sample_fs();
+        let d = fs.lookup("/d").unwrap();
+
+        let plan = plan_deletion(&fs, 8_381_165, false).unwrap();
+        assert_eq!(
+            (plan.nodes.as_slice(), plan.freed),
+            ([d].as_slice(), 24_933_642)
+        );
+        assert_eq!(plan.smallest_directory, Some(d));
+
+        for target in [1, 584, 8_095_616, 8_381_165, 20_000_000, 48_381_165] {
+            let plan = plan_deletion(&fs, target, true).unwrap();
+            assert!(plan.exhaustive);
+            assert_eq!(
+                Some(plan.freed),
+                least_freed(&fs, target, true),
+                "{}",
+                target
+            );
+            let sizes: usize = plan.nodes.iter().map(|&id| fs.size(id)).sum();
+            assert_eq!(sizes, plan.freed);
+        }
+        // /d/d.log and /a/h.lst add up to exactly this
+        assert_eq!(
+            plan_deletion(&fs, 8_095_616, true).unwrap().freed,
+            8_095_616
+        );
+
+        assert!(plan_deletion(&fs, 0, true).unwrap().nodes.is_empty());
+        assert_eq!(plan_deletion(&fs, 48_381_166, true), None);
+
+        let explained = plan_deletion(&fs, 8_381_165, true).unwrap().explain(&fs);
+        assert!(
+            explained.starts_with("to free 8381165, delete:\n"),
+            "{}",
+            explained
+        );
+        assert!(
+            explained.contains("deleting just /d would free 24933642"),
+            "{}",
+            explained
+        );
+    }
 }
@@ -1,121 +1,169 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc;
+use crate::{
+    error::Result,
+    export::{heat, Export, Image, BLACK},
+    generate::{join_lines, Generate, Random},
+    grid::{Grid, Pos, NEIGHBOURS4},
+    input::NumberedLines,
+    reference::Reference,
+    solution::Solution,
+};
+use rand::Rng;
 use std::io::BufRead;
 
-fn visible_trees_in<'a, I: IntoIterator<Item = &'a u8>>(cur_height: u8, iter: I) -> usize {
-    let mut count = 0;
-    for &height in iter {
-        count += 1;
-        if height >= cur_height {
-            break;
+/// How many trees can be seen from `pos` in each direction, stopping at the first one at least
+/// as tall.
+fn viewing_distances(grid: &Grid<u8>, pos: Pos) -> impl Iterator<Item = usize> + '_ {
+    let cur_height = grid[pos];
+    NEIGHBOURS4.iter().map(move |&direction| {
+        let mut count = 0;
+        for (_, &height) in grid.ray(pos, direction) {
+            count += 1;
+            if height >= cur_height {
+                break;
+            }
         }
-    }
-    count
+        count
+    })
 }
 
-#[allow(clippy::many_single_char_names)]
-fn visible_trees(data: &[Vec<u8>], i: usize, j: usize) -> usize {
-    let cur_height = data[i][j];
-
-    let l = visible_trees_in(cur_height, data[i][0..j].iter().rev());
-    let r = visible_trees_in(cur_height, &data[i][j + 1..]);
-    let u = visible_trees_in(cur_height, (0..i).rev().map(|v| &data[v][j]));
-    let d = visible_trees_in(cur_height, (i + 1..data.len()).map(|v| &data[v][j]));
-
-    u * d * l * r
+fn is_visible(grid: &Grid<u8>, pos: Pos) -> bool {
+    let cur_height = grid[pos];
+    NEIGHBOURS4.iter().any(|&direction| {
+        grid.ray(pos, direction)
+            .all(|(_, &height)| height < cur_height)
+    })
 }
-fn solve(filename: &str) -> aoc::Result<(usize, usize)> {
-    let reader = aoc::file(filename)?;
 
-    let grid: Vec<Vec<u8>> = reader
-        .lines()
-        .map(|line| line.unwrap().bytes().map(|byte| byte - b'0').collect())
-        .collect();
+pub struct Day8;
 
-    let height = grid.len();
-    let width = grid[0].len();
+impl Solution for Day8 {
+    const DAY: u32 = 8;
 
-    let mut visible: Vec<Vec<bool>> = vec![vec![false; width]; height];
+    type Input = Grid<u8>;
+    type Params = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    // for each row walk across both ways, handling the visiblity grid
-    for (i, row) in grid.iter().enumerate() {
-        assert_eq!(row.len(), width);
-        let mut left_tallest = 0;
-        let mut right_tallest = 0;
-
-        visible[i][0] = true;
-        visible[i][width - 1] = true;
-
-        for j in 0..width {
-            let inv_j = width - j - 1;
-            if row[j] > left_tallest {
-                visible[i][j] = true;
-                left_tallest = row[j];
-            }
-            if row[inv_j] > right_tallest {
-                visible[i][inv_j] = true;
-                right_tallest = row[inv_j];
-            }
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        let mut lines = NumberedLines::new(Self::DAY, reader);
+        let grid = Grid::parse(&mut lines, "a row of digits", |_, ch| {
+            ch.to_digit(10).and_then(|height| u8::try_from(height).ok())
+        })?;
+        // part 2 wants the best tree, so there has to be one
+        if grid.width() == 0 {
+            return Err(lines.end_of_input("a row of digits").into());
         }
+        Ok(grid)
     }
 
-    // for each column walk across both ways, handling the visiblity grid
-    for j in 0..width {
-        let mut top_tallest = 0;
-        let mut bottom_tallest = 0;
+    fn part1(grid: &Self::Input, _params: &()) -> usize {
+        grid.positions()
+            .filter(|&pos| is_visible(grid, pos))
+            .count()
+    }
 
-        visible[0][j] = true;
-        visible[height - 1][j] = true;
+    fn part2(grid: &Self::Input, _params: &()) -> usize {
+        grid.positions()
+            .map(|pos| viewing_distances(grid, pos).product())
+            .max()
+            .expect("checked when parsing")
+    }
+}
 
-        for i in 0..height {
-            let inv_i = height - i - 1;
-            if grid[i][j] > top_tallest {
-                visible[i][j] = true;
-                top_tallest = grid[i][j];
-            }
-            if grid[inv_i][j] > bottom_tallest {
-                visible[inv_i][j] = true;
-                bottom_tallest = grid[inv_i][j];
+/// Each tree shaded by its scenic score, and dimmed if it can't be seen from outside the forest.
+impl Export for Day8 {
+    fn snapshot(grid: &Self::Input, _params: &()) -> Image {
+        let scores: Vec<(Pos, u64)> = grid
+            .positions()
+            .map(|pos| (pos, viewing_distances(grid, pos).product::<usize>() as u64))
+            .collect();
+        let max = scores.iter().map(|(_, score)| *score).max().unwrap_or(0);
+
+        let mut image = Image::new(grid.width(), grid.height(), BLACK);
+        for (pos, score) in scores {
+            let mut colour = heat(score, max);
+            if !is_visible(grid, pos) {
+                colour = colour.map(|channel| channel / 3);
             }
+            image.set(pos, colour);
         }
+        image
     }
+}
 
-    Ok((
-        visible
-            .iter()
-            .map(|row| row.iter().filter(|v| **v).count())
-            .sum(),
-        (0..height)
-            .map(|i| {
-                (0..width)
-                    .map(|j| visible_trees(&grid, i, j))
-                    .max()
-                    .unwrap()
-            })
-            .max()
-            .unwrap(),
-    ))
+/// A square forest `size` trees across.
+impl Generate for Day8 {
+    const SIZE: usize = 99;
+
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        let width = size.max(1);
+        join_lines((0..width).map(|_| {
+            (0..width)
+                .map(|_| char::from(b'0' + rng.gen_range(0..=9)))
+                .collect()
+        }))
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day8")?;
+/// The trees between `pos` and each edge, nearest first, read straight out of the grid by index.
+fn lines_of_sight(grid: &Grid<u8>, (row, col): Pos) -> [Vec<u8>; 4] {
+    [
+        (0..row).rev().map(|r| grid[(r, col)]).collect(),
+        (row + 1..grid.height()).map(|r| grid[(r, col)]).collect(),
+        (0..col).rev().map(|c| grid[(row, c)]).collect(),
+        (col + 1..grid.width()).map(|c| grid[(row, c)]).collect(),
+    ]
+}
 
-    println!("{}", part1);
-    println!("{}", part2);
+/// Casts a line of sight from every tree separately.
+impl Reference for Day8 {
+    fn reference1(grid: &Self::Input, _params: &()) -> Option<usize> {
+        let visible = grid.positions().filter(|&pos| {
+            lines_of_sight(grid, pos)
+                .iter()
+                .any(|trees| trees.iter().all(|&height| height < grid[pos]))
+        });
+        Some(visible.count())
+    }
 
-    Ok(())
+    fn reference2(grid: &Self::Input, _params: &()) -> Option<usize> {
+        grid.positions()
+            .map(|pos| {
+                lines_of_sight(grid, pos)
+                    .iter()
+                    .map(|trees| match trees.iter().position(|&h| h >= grid[pos]) {
+                        Some(blocker) => blocker + 1,
+                        None => trees.len(),
+                    })
+                    .product()
+            })
+            .max()
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day8;
+    use crate::{
+        error::Error,
+        solution::{solve, solve_file},
+    };
 
     #[test]
     fn sample() {
-        let (part1, part2) = solve("inputs-sample/day8").unwrap();
+        let (part1, part2) = solve_file::<Day8>("inputs-sample/day8").unwrap();
 
         assert_eq!(part1, 21);
         assert_eq!(part2, 8);
     }
+
+    #[test]
+    fn malformed() {
+        for (input, line) in [("", 1), ("\n", 2), ("30373\n2551\n", 2), ("3a\n", 1)] {
+            match solve::<Day8, _>(input.as_bytes()) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line, "{:?}", input),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
 }
@@ -1,14 +1,20 @@
-#![warn(clippy::pedantic)]
-#![allow(clippy::uninlined_format_args)]
-use adventlib::aoc::{
-    self,
-    point2d::{Direction, Point2D},
+use crate::{
+    error::Result,
+    generate::{join_lines, Generate, Random},
+    input::NumberedLines,
+    solution::Solution,
+    visualize::{Animator, Frame, Visualize},
 };
-use std::convert::TryInto;
-use std::{collections::HashSet, io::BufRead};
+use adventlib::aoc::point2d::{Direction, Point2D};
+use rand::Rng;
+use std::{collections::HashSet, io, io::BufRead};
+
+/// How far either side of the head [`Rope::render`] shows.
+const VIEW_WIDTH: i64 = 30;
+const VIEW_HEIGHT: i64 = 10;
 
 #[derive(Debug)]
-struct Instruction(Direction, u32);
+pub struct Instruction(Direction, u32);
 
 #[derive(Debug)]
 struct Rope {
@@ -51,54 +57,120 @@ impl Rope {
         }
         *self.knots.last().unwrap()
     }
+
+    /// Draws the area around the head like the puzzle does, with `#` wherever the tail has been.
+    fn render(&self, visited: &HashSet<Point2D>) -> String {
+        let mut out = String::new();
+        for y in (self.head.1 - VIEW_HEIGHT..=self.head.1 + VIEW_HEIGHT).rev() {
+            for x in self.head.0 - VIEW_WIDTH..=self.head.0 + VIEW_WIDTH {
+                let point = Point2D(x, y);
+                out.push(if point == self.head {
+                    'H'
+                } else if let Some(i) = self.knots.iter().position(|knot| *knot == point) {
+                    u32::try_from(i + 1)
+                        .ok()
+                        .and_then(|n| char::from_digit(n, 10))
+                        .unwrap_or('T')
+                } else if point == Point2D(0, 0) {
+                    's'
+                } else if visited.contains(&point) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
 }
 
-fn solve(filename: &str) -> aoc::Result<(usize, usize)> {
-    let reader = aoc::file(filename)?;
-    let instructions: Vec<Instruction> = reader
-        .lines()
-        .map(|line| {
-            let line = line?;
-            let mut parts = line.split(' ');
+fn tail_visited(instructions: &[Instruction], knots: usize) -> usize {
+    let mut visited: HashSet<Point2D> = HashSet::new();
+
+    let mut rope = Rope::new(knots);
+    for inst in instructions {
+        for _ in 0..inst.1 {
+            visited.insert(rope.step(inst.0));
+        }
+    }
 
-            let direction = parts.next().unwrap().try_into()?;
-            let count = parts.next().unwrap().parse()?;
+    visited.len()
+}
 
-            Ok(Instruction(direction, count))
-        })
-        .collect::<Result<Vec<Instruction>, aoc::Error>>()?;
+pub struct Day9;
 
-    let mut short_visited: HashSet<Point2D> = HashSet::new();
-    let mut long_visited: HashSet<Point2D> = HashSet::new();
+impl Solution for Day9 {
+    const DAY: u32 = 9;
 
-    let mut short_rope = Rope::new(2);
-    let mut long_rope = Rope::new(10);
-    for inst in &instructions {
-        for _ in 0..inst.1 {
-            short_visited.insert(short_rope.step(inst.0));
-            long_visited.insert(long_rope.step(inst.0));
-        }
+    type Input = Vec<Instruction>;
+    type Params = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+        NumberedLines::new(Self::DAY, reader)
+            .map(|line| {
+                let line = line?;
+                let instruction = line.text.split_once(' ').and_then(|(direction, count)| {
+                    Some(Instruction(direction.try_into().ok()?, count.parse().ok()?))
+                });
+
+                instruction.ok_or_else(|| line.error("`<U|D|L|R> <steps>`").into())
+            })
+            .collect()
+    }
+
+    fn part1(instructions: &Self::Input, _params: &()) -> usize {
+        tail_visited(instructions, 2)
     }
 
-    Ok((short_visited.len(), long_visited.len()))
+    fn part2(instructions: &Self::Input, _params: &()) -> usize {
+        tail_visited(instructions, 10)
+    }
 }
 
-fn main() -> aoc::Result<()> {
-    let (part1, part2) = solve("inputs/day9")?;
+/// Moves the ten knot rope from part 2, one frame per step.
+impl Visualize for Day9 {
+    fn visualize(
+        instructions: &Self::Input,
+        _params: &(),
+        animator: &mut Animator<'_>,
+    ) -> io::Result<()> {
+        let mut visited: HashSet<Point2D> = HashSet::new();
+
+        let mut rope = Rope::new(10);
+        for inst in instructions {
+            for _ in 0..inst.1 {
+                visited.insert(rope.step(inst.0));
+                animator.frame(|| Frame::Text(rope.render(&visited)))?;
+            }
+        }
 
-    println!("{}", part1);
-    println!("{}", part2);
+        animator.finish(&Frame::Text(rope.render(&visited)))
+    }
+}
 
-    Ok(())
+/// `size` moves of the head.
+impl Generate for Day9 {
+    const SIZE: usize = 2000;
+
+    fn generate(rng: &mut Random, size: usize, _params: &()) -> String {
+        join_lines((0..size.max(1)).map(|_| {
+            let direction = ['U', 'D', 'L', 'R'][rng.gen_range(0..4)];
+            format!("{} {}", direction, rng.gen_range(1..=20))
+        }))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::solve;
+    use super::Day9;
+    use crate::solution::solve_file;
 
     #[test]
     fn sample1() {
-        let (part1, part2) = solve("inputs-sample/day9-sample1").unwrap();
+        let (part1, part2) = solve_file::<Day9>("inputs-sample/day9-sample1").unwrap();
 
         assert_eq!(part1, 13);
         assert_eq!(part2, 1);
@@ -106,7 +178,7 @@ mod test {
 
     #[test]
     fn sample2() {
-        let (_, part2) = solve("inputs-sample/day9-sample2").unwrap();
+        let (_, part2) = solve_file::<Day9>("inputs-sample/day9-sample2").unwrap();
 
         assert_eq!(part2, 36);
     }
@@ -0,0 +1,406 @@
+//! An interactive debugger for day 10's CRT CPU.
+use crate::day10::{ExecutionContext, Instruction};
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+    mem,
+};
+
+/// Where `continue` stops. Breakpoints are checked before each cycle runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Before the given 1-based cycle.
+    Cycle(usize),
+    /// When the given instruction starts.
+    Pc(usize),
+    /// When X holds the given value.
+    XEquals(i32),
+    /// When X changes.
+    Watch,
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Cycle(cycle) => write!(f, "cycle {}", cycle),
+            Breakpoint::Pc(pc) => write!(f, "pc {}", pc),
+            Breakpoint::XEquals(x) => write!(f, "x == {}", x),
+            Breakpoint::Watch => write!(f, "watch x"),
+        }
+    }
+}
+
+/// Part of the state to print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Cycle,
+    Pc,
+    X,
+    Instruction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Run this many cycles, ignoring breakpoints.
+    Step(usize),
+    /// Run until a breakpoint hits or the program finishes.
+    Continue,
+    Break(Breakpoint),
+    /// Remove the breakpoint with this 1-based number.
+    Delete(usize),
+    /// List the breakpoints.
+    Breakpoints,
+    /// Print one field, or the whole state.
+    Print(Option<Field>),
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Parses one line of debugger input. `x==4` and `x == 4` are both accepted.
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let command = words.next()?;
+        let rest: Vec<&str> = words.collect();
+        match (command, rest.as_slice()) {
+            ("step" | "s", []) => Some(Command::Step(1)),
+            ("step" | "s", [n]) => n.parse().ok().map(Command::Step),
+            ("continue" | "c", []) => Some(Command::Continue),
+            ("break" | "b", args) => parse_breakpoint(&args.concat()).map(Command::Break),
+            ("watch", ["x"]) => Some(Command::Break(Breakpoint::Watch)),
+            ("delete" | "d", [n]) => n.parse().ok().map(Command::Delete),
+            ("breakpoints" | "info", []) => Some(Command::Breakpoints),
+            ("print" | "p", []) => Some(Command::Print(None)),
+            ("print" | "p", [field]) => {
+                let field = match *field {
+                    "cycle" => Field::Cycle,
+                    "pc" => Field::Pc,
+                    "x" => Field::X,
+                    "instruction" => Field::Instruction,
+                    _ => return None,
+                };
+                Some(Command::Print(Some(field)))
+            }
+            ("help" | "h", []) => Some(Command::Help),
+            ("quit" | "q", []) => Some(Command::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a breakpoint with its words run together: `cycle20`, `pc3` or `x==4`.
+fn parse_breakpoint(spec: &str) -> Option<Breakpoint> {
+    if let Some(x) = spec.strip_prefix("x==") {
+        x.parse().ok().map(Breakpoint::XEquals)
+    } else if let Some(cycle) = spec.strip_prefix("cycle") {
+        cycle.parse().ok().map(Breakpoint::Cycle)
+    } else if let Some(pc) = spec.strip_prefix("pc") {
+        pc.parse().ok().map(Breakpoint::Pc)
+    } else {
+        None
+    }
+}
+
+const HELP: &str = "\
+step [n]            run n cycles (default 1)
+continue            run until a breakpoint or the end of the program
+break cycle <n>     stop before cycle n
+break pc <n>        stop when instruction n starts
+break x == <n>      stop when X is n
+watch x             stop when X changes
+delete <n>          remove breakpoint n
+breakpoints         list breakpoints
+print [cycle|pc|x|instruction]
+quit";
+
+/// Steps through a program, stopping at breakpoints.
+pub struct Debugger<'a> {
+    execution: ExecutionContext<'a>,
+    breakpoints: Vec<Breakpoint>,
+    /// Whether any cycle has run, so breakpoints on the state before the first one have had their
+    /// chance.
+    started: bool,
+}
+
+impl<'a> Debugger<'a> {
+    #[must_use]
+    pub fn new(program: &'a [Instruction]) -> Self {
+        Self {
+            execution: ExecutionContext::new(program),
+            breakpoints: Vec::new(),
+            started: false,
+        }
+    }
+
+    #[must_use]
+    pub fn execution(&self) -> &ExecutionContext<'a> {
+        &self.execution
+    }
+
+    /// The current instruction, cycle and register on one line.
+    #[must_use]
+    pub fn status(&self) -> String {
+        let execution = &self.execution;
+        let x = execution.register().x;
+        match execution.instruction() {
+            Some((instruction, stage)) => format!(
+                "cycle {}: pc {} `{}` ({} of {}), X = {}",
+                execution.cycle(),
+                execution.pc(),
+                instruction,
+                stage + 1,
+                instruction.cycles(),
+                x
+            ),
+            None => format!("cycle {}: finished, X = {}", execution.cycle(), x),
+        }
+    }
+
+    /// The 1-based number of the first breakpoint that holds at the current state. `Watch`
+    /// compares against X before the last cycle.
+    fn hit(&self, previous_x: i32) -> Option<usize> {
+        let execution = &self.execution;
+        let x = execution.register().x;
+        let starting = execution.instruction().is_some_and(|(_, stage)| stage == 0);
+        self.breakpoints
+            .iter()
+            .position(|breakpoint| match *breakpoint {
+                Breakpoint::Cycle(cycle) => execution.cycle() == cycle,
+                Breakpoint::Pc(pc) => starting && execution.pc() == pc,
+                Breakpoint::XEquals(value) => x == value,
+                Breakpoint::Watch => x != previous_x,
+            })
+            .map(|index| index + 1)
+    }
+
+    fn stopped_at(&self, number: usize) -> String {
+        format!(
+            "breakpoint {} ({}) hit\n{}",
+            number,
+            self.breakpoints[number - 1],
+            self.status()
+        )
+    }
+
+    /// Runs a command, returning what to print, or `None` to quit.
+    pub fn execute(&mut self, command: Command) -> Option<String> {
+        let output = match command {
+            Command::Step(cycles) => {
+                self.started |= cycles > 0;
+                for _ in 0..cycles {
+                    self.execution.next();
+                }
+                self.status()
+            }
+            Command::Continue => {
+                if !mem::replace(&mut self.started, true) {
+                    // nothing has run yet, so X hasn't changed either
+                    if let Some(number) = self.hit(self.execution.register().x) {
+                        return Some(self.stopped_at(number));
+                    }
+                }
+                // otherwise always run at least one cycle, so continuing from a breakpoint moves on
+                loop {
+                    let previous_x = self.execution.register().x;
+                    self.execution.next();
+                    if let Some(number) = self.hit(previous_x) {
+                        break self.stopped_at(number);
+                    }
+                    if self.execution.is_finished() {
+                        break self.status();
+                    }
+                }
+            }
+            Command::Break(breakpoint) => {
+                self.breakpoints.push(breakpoint);
+                format!("breakpoint {}: {}", self.breakpoints.len(), breakpoint)
+            }
+            Command::Delete(number) => {
+                if (1..=self.breakpoints.len()).contains(&number) {
+                    let breakpoint = self.breakpoints.remove(number - 1);
+                    format!("deleted breakpoint {}: {}", number, breakpoint)
+                } else {
+                    format!("no breakpoint {}", number)
+                }
+            }
+            Command::Breakpoints => {
+                if self.breakpoints.is_empty() {
+                    "no breakpoints".to_string()
+                } else {
+                    self.breakpoints
+                        .iter()
+                        .enumerate()
+                        .map(|(index, breakpoint)| format!("{}: {}", index + 1, breakpoint))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
+            }
+            Command::Print(None) => self.status(),
+            Command::Print(Some(field)) => {
+                let execution = &self.execution;
+                match field {
+                    Field::Cycle => execution.cycle().to_string(),
+                    Field::Pc => execution.pc().to_string(),
+                    Field::X => execution.register().x.to_string(),
+                    Field::Instruction => execution
+                        .instruction()
+                        .map_or_else(|| "finished".to_string(), |(i, _)| i.to_string()),
+                }
+            }
+            Command::Help => HELP.to_string(),
+            Command::Quit => return None,
+        };
+        Some(output)
+    }
+
+    /// Reads commands from `input` until `quit` or the end of input, writing a prompt before each
+    /// and the result after. An empty line repeats the previous command.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        writeln!(output, "{}", self.status())?;
+        let mut previous = None;
+        let mut lines = input.lines();
+        loop {
+            write!(output, "(crt) ")?;
+            output.flush()?;
+            let Some(line) = lines.next().transpose()? else {
+                writeln!(output)?;
+                return Ok(());
+            };
+
+            let command = if line.trim().is_empty() {
+                previous
+            } else {
+                Command::parse(&line)
+            };
+            match command {
+                Some(command) => match self.execute(command) {
+                    Some(text) => writeln!(output, "{}", text)?,
+                    None => return Ok(()),
+                },
+                None => writeln!(output, "unknown command `{}`; try `help`", line.trim())?,
+            }
+            previous = command.or(previous);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Breakpoint, Command, Debugger, Field};
+    use crate::{day10::Day10, solution::Solution};
+    use adventlib::aoc;
+
+    #[test]
+    fn commands() {
+        assert_eq!(Command::parse("step"), Some(Command::Step(1)));
+        assert_eq!(Command::parse("  s 5 "), Some(Command::Step(5)));
+        assert_eq!(
+            Command::parse("break cycle 20"),
+            Some(Command::Break(Breakpoint::Cycle(20)))
+        );
+        assert_eq!(
+            Command::parse("b pc 3"),
+            Some(Command::Break(Breakpoint::Pc(3)))
+        );
+        for line in ["break x == -1", "break x==-1", "b x ==-1"] {
+            assert_eq!(
+                Command::parse(line),
+                Some(Command::Break(Breakpoint::XEquals(-1))),
+                "{}",
+                line
+            );
+        }
+        assert_eq!(
+            Command::parse("watch x"),
+            Some(Command::Break(Breakpoint::Watch))
+        );
+        assert_eq!(Command::parse("p x"), Some(Command::Print(Some(Field::X))));
+        assert_eq!(Command::parse("delete 2"), Some(Command::Delete(2)));
+
+        for line in ["", "step two", "break", "break y == 1", "print y", "jump 3"] {
+            assert_eq!(Command::parse(line), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn breakpoints() {
+        let program = Day10::parse(aoc::file("inputs-sample/day10-sample1").unwrap()).unwrap();
+        let mut debugger = Debugger::new(&program);
+        assert_eq!(debugger.status(), "cycle 1: pc 0 `noop` (1 of 1), X = 1");
+
+        debugger.execute(Command::Break(Breakpoint::Pc(1)));
+        debugger.execute(Command::Break(Breakpoint::XEquals(4)));
+        assert_eq!(
+            debugger.execute(Command::Continue).unwrap(),
+            "breakpoint 1 (pc 1) hit\ncycle 2: pc 1 `addx 3` (1 of 2), X = 1"
+        );
+        assert_eq!(
+            debugger.execute(Command::Continue).unwrap(),
+            "breakpoint 2 (x == 4) hit\ncycle 4: pc 2 `addx -5` (1 of 2), X = 4"
+        );
+        assert_eq!(
+            debugger.execute(Command::Continue).unwrap(),
+            "breakpoint 2 (x == 4) hit\ncycle 5: pc 2 `addx -5` (2 of 2), X = 4",
+            "hits again while it holds"
+        );
+        assert_eq!(
+            debugger.execute(Command::Continue).unwrap(),
+            "cycle 6: finished, X = -1",
+            "stops at the end"
+        );
+
+        let mut debugger = Debugger::new(&program);
+        debugger.execute(Command::Break(Breakpoint::Cycle(1)));
+        debugger.execute(Command::Break(Breakpoint::Pc(0)));
+        assert_eq!(
+            debugger.execute(Command::Continue).unwrap(),
+            "breakpoint 1 (cycle 1) hit\ncycle 1: pc 0 `noop` (1 of 1), X = 1",
+            "stops before the first cycle runs"
+        );
+        assert_eq!(
+            debugger.execute(Command::Continue).unwrap(),
+            "cycle 6: finished, X = -1",
+            "moves on from there"
+        );
+
+        let mut debugger = Debugger::new(&program);
+        debugger.execute(Command::Break(Breakpoint::Watch));
+        assert_eq!(
+            debugger.execute(Command::Continue).unwrap(),
+            "breakpoint 1 (watch x) hit\ncycle 4: pc 2 `addx -5` (1 of 2), X = 4"
+        );
+        assert_eq!(debugger.execute(Command::Quit), None);
+    }
+
+    #[test]
+    fn repl() {
+        let program = Day10::parse(aoc::file("inputs-sample/day10-sample2").unwrap()).unwrap();
+        let mut debugger = Debugger::new(&program);
+        let mut output = Vec::new();
+        debugger
+            .run(
+                "break cycle 20\ncontinue\np x\n\nstep 2\nbogus\n\nbreakpoints\ndelete 1\nquit\nstep\n"
+                    .as_bytes(),
+                &mut output,
+            )
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "cycle 1: pc 0 `addx 15` (1 of 2), X = 1\n\
+             (crt) breakpoint 1: cycle 20\n\
+             (crt) breakpoint 1 (cycle 20) hit\n\
+             cycle 20: pc 10 `addx -1` (1 of 2), X = 21\n\
+             (crt) 21\n\
+             (crt) 21\n\
+             (crt) cycle 22: pc 11 `addx 5` (1 of 2), X = 20\n\
+             (crt) unknown command `bogus`; try `help`\n\
+             (crt) cycle 24: pc 12 `addx -1` (1 of 2), X = 25\n\
+             (crt) 1: cycle 20\n\
+             (crt) deleted breakpoint 1: cycle 20\n\
+             (crt) "
+        );
+        assert_eq!(debugger.execution().cycle(), 24, "stops reading at quit");
+    }
+}
@@ -0,0 +1,129 @@
+use adventlib::aoc;
+use std::{fmt, io};
+
+/// A line of puzzle input that doesn't have the shape the day expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u32,
+    /// 1-based, as an editor would show it.
+    pub line: usize,
+    /// The offending line, or `None` if the input ended while more was expected.
+    pub text: Option<String>,
+    /// What the line should have looked like.
+    pub expected: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {}, line {}: expected {}, ",
+            self.day, self.line, self.expected
+        )?;
+        match &self.text {
+            Some(text) => write!(f, "found {:?}", text),
+            None => write!(f, "found end of input"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug)]
+pub enum Error {
+    Aoc(aoc::Error),
+    Io(io::Error),
+    Parse(ParseError),
+    Http(Box<ureq::Error>),
+    Png(png::EncodingError),
+    Gif(gif::EncodingError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Aoc(e) => write!(f, "{:?}", e),
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Http(e) => write!(f, "{}", e),
+            Self::Png(e) => write!(f, "{}", e),
+            Self::Gif(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Aoc(_) => None,
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Http(e) => Some(e.as_ref()),
+            Self::Png(e) => Some(e),
+            Self::Gif(e) => Some(e),
+        }
+    }
+}
+
+impl From<aoc::Error> for Error {
+    fn from(e: aoc::Error) -> Self {
+        Self::Aoc(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(e: ureq::Error) -> Self {
+        Self::Http(Box::new(e))
+    }
+}
+
+impl From<png::EncodingError> for Error {
+    fn from(e: png::EncodingError) -> Self {
+        Self::Png(e)
+    }
+}
+
+impl From<gif::EncodingError> for Error {
+    fn from(e: gif::EncodingError) -> Self {
+        Self::Gif(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use super::ParseError;
+
+    #[test]
+    fn display() {
+        let mut error = ParseError {
+            day: 5,
+            line: 12,
+            text: Some("mvoe 1 from 2 to 1".to_string()),
+            expected: "`move <n> from <stack> to <stack>`".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "day 5, line 12: expected `move <n> from <stack> to <stack>`, found \"mvoe 1 from 2 to 1\""
+        );
+
+        error.text = None;
+        assert_eq!(
+            error.to_string(),
+            "day 5, line 12: expected `move <n> from <stack> to <stack>`, found end of input"
+        );
+    }
+}
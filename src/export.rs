@@ -0,0 +1,311 @@
+use crate::{
+    day12, day14, day17, day8,
+    error::Result,
+    grid::{Grid, Pos},
+    solution::Solution,
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufWriter, Write},
+    path::Path,
+};
+
+/// A colour as red, green and blue.
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// A picture in memory, one colour per pixel, ready to be written out as a PNG or a GIF frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Grid<Rgb>,
+}
+
+impl Image {
+    #[must_use]
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Self {
+            pixels: Grid::new(width, height, fill),
+        }
+    }
+
+    /// One pixel per cell of `grid`, coloured by `colour`.
+    pub fn from_grid<T>(grid: &Grid<T>, colour: impl FnMut(&T) -> Rgb) -> Self {
+        Self {
+            pixels: grid.map(colour),
+        }
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.pixels.width()
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.pixels.height()
+    }
+
+    pub fn set(&mut self, pos: Pos, colour: Rgb) {
+        self.pixels[pos] = colour;
+    }
+
+    /// Blows every pixel up into a `factor` by `factor` square, since most puzzles are tiny.
+    #[must_use]
+    pub fn scaled(&self, factor: usize) -> Self {
+        let factor = factor.max(1);
+        let mut scaled = Self::new(self.width() * factor, self.height() * factor, BLACK);
+        for pos in scaled.pixels.positions() {
+            scaled.pixels[pos] = self.pixels[(pos.0 / factor, pos.1 / factor)];
+        }
+        scaled
+    }
+
+    fn rgb_bytes(&self) -> Vec<u8> {
+        self.pixels.iter().flatten().copied().collect()
+    }
+
+    pub fn write_png(&self, out: impl Write) -> Result<()> {
+        let mut encoder = png::Encoder::new(
+            out,
+            u32::try_from(self.width()).expect("image too wide"),
+            u32::try_from(self.height()).expect("image too tall"),
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.rgb_bytes())?;
+        Ok(())
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<()> {
+        self.write_png(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// Shades `value` out of `max` from dark blue through green to red.
+#[must_use]
+pub fn heat(value: u64, max: u64) -> Rgb {
+    let t = (value.min(max) * 510).checked_div(max).unwrap_or(0);
+    let channel = |v: u64| u8::try_from(v.min(255)).unwrap_or(u8::MAX);
+
+    if t < 255 {
+        [0, channel(t), channel(255 - t)]
+    } else {
+        [channel(t - 255), channel(510 - t), 0]
+    }
+}
+
+/// Writes an animated GIF that loops forever, one [`Image`] per frame.
+///
+/// Every frame has to be the same size as the first.
+pub struct GifWriter<W: Write> {
+    out: Option<W>,
+    encoder: Option<gif::Encoder<W>>,
+    size: (u16, u16),
+    /// Time each frame is shown for, in hundredths of a second.
+    delay: u16,
+}
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(out: W, delay: u16) -> Self {
+        Self {
+            out: Some(out),
+            encoder: None,
+            size: (0, 0),
+            delay,
+        }
+    }
+
+    pub fn frame(&mut self, image: &Image) -> Result<()> {
+        let too_big = || io::Error::new(io::ErrorKind::InvalidInput, "image too large for a GIF");
+        let size = (
+            u16::try_from(image.width()).map_err(|_| too_big())?,
+            u16::try_from(image.height()).map_err(|_| too_big())?,
+        );
+
+        if let Some(out) = self.out.take() {
+            let mut encoder = gif::Encoder::new(out, size.0, size.1, &[])?;
+            encoder.set_repeat(gif::Repeat::Infinite)?;
+            self.encoder = Some(encoder);
+            self.size = size;
+        } else if size != self.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "every GIF frame has to be the same size",
+            )
+            .into());
+        }
+
+        let mut frame = gif::Frame::from_rgb_speed(size.0, size.1, &image.rgb_bytes(), 10);
+        frame.delay = self.delay;
+        self.encoder
+            .as_mut()
+            .expect("set up by the first frame")
+            .write_frame(&frame)?;
+        Ok(())
+    }
+
+    /// Finishes the file, returning what it was written to; `None` if there were no frames.
+    pub fn finish(self) -> Result<Option<W>> {
+        match self.encoder {
+            Some(encoder) => Ok(Some(encoder.into_inner()?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// How images are exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Size of each puzzle cell, in pixels.
+    pub scale: usize,
+    /// Only every `every`th step of a simulation becomes a GIF frame.
+    pub every: usize,
+    /// Time each GIF frame is shown for, in hundredths of a second.
+    pub delay: u16,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            scale: 4,
+            every: 1,
+            delay: 5,
+        }
+    }
+}
+
+/// A day that can be drawn as a picture.
+pub trait Export: Solution {
+    /// The finished puzzle.
+    fn snapshot(input: &Self::Input, params: &Self::Params) -> Image;
+
+    /// The simulation as it runs, handing every `every`th step to `frame`. Days with nothing to
+    /// animate just produce their snapshot.
+    fn frames(
+        input: &Self::Input,
+        params: &Self::Params,
+        _every: usize,
+        frame: &mut dyn FnMut(&Image) -> Result<()>,
+    ) -> Result<()> {
+        frame(&Self::snapshot(input, params))
+    }
+}
+
+/// Exports the puzzle input read from the reader to a PNG or GIF, depending on the extension.
+pub type Exporter = fn(&mut dyn BufRead, &Options, &Path) -> Result<()>;
+
+/// Parses `reader` and writes a snapshot to `path` if it ends in `.png`, or an animation if it
+/// ends in `.gif`.
+pub fn export<S: Export>(reader: &mut dyn BufRead, options: &Options, path: &Path) -> Result<()> {
+    let input = S::parse(reader)?;
+    let params = S::Params::default();
+
+    if path.extension().is_some_and(|ext| ext == "gif") {
+        let mut gif = GifWriter::new(BufWriter::new(File::create(path)?), options.delay);
+        S::frames(&input, &params, options.every.max(1), &mut |image| {
+            gif.frame(&image.scaled(options.scale))
+        })?;
+        if let Some(mut out) = gif.finish()? {
+            out.flush()?;
+        }
+        Ok(())
+    } else {
+        S::snapshot(&input, &params)
+            .scaled(options.scale)
+            .save_png(path)
+    }
+}
+
+/// Every day that can be exported, in order.
+pub const EXPORTERS: &[(u32, Exporter)] = &[
+    (8, export::<day8::Day8>),
+    (12, export::<day12::Day12>),
+    (14, export::<day14::Day14>),
+    (17, export::<day17::Day17>),
+];
+
+#[must_use]
+pub fn exporter(day: u32) -> Option<Exporter> {
+    EXPORTERS
+        .iter()
+        .find(|(registered, _)| *registered == day)
+        .map(|(_, export)| *export)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{exporter, heat, GifWriter, Image, Options, BLACK, WHITE};
+    use std::{env, fs, process};
+
+    #[test]
+    fn png() {
+        let mut image = Image::new(3, 2, BLACK);
+        image.set((1, 2), WHITE);
+        let image = image.scaled(2);
+
+        let mut out = Vec::new();
+        image.write_png(&mut out).unwrap();
+
+        let mut reader = png::Decoder::new(out.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (6, 4));
+        // the bottom right corner is white, the top left black
+        assert_eq!(&pixels[pixels.len() - 3..], &WHITE);
+        assert_eq!(&pixels[..3], &BLACK);
+
+        assert_eq!(heat(0, 10), [0, 0, 255]);
+        assert_eq!(heat(10, 10), [255, 0, 0]);
+    }
+
+    #[test]
+    fn gif() {
+        let mut gif = GifWriter::new(Vec::new(), 5);
+        gif.frame(&Image::new(4, 3, BLACK)).unwrap();
+        gif.frame(&Image::new(4, 3, WHITE)).unwrap();
+        assert!(gif.frame(&Image::new(3, 3, WHITE)).is_err());
+        let out = gif.finish().unwrap().unwrap();
+
+        let mut decoder = gif::DecodeOptions::new().read_info(out.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (4, 3));
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 2);
+
+        assert!(GifWriter::new(Vec::new(), 5).finish().unwrap().is_none());
+    }
+
+    #[test]
+    fn samples() {
+        let dir = env::temp_dir().join(format!("aoc2022-export-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = Options {
+            scale: 1,
+            every: 5,
+            delay: 1,
+        };
+
+        for (day, sample) in [(8, "day8"), (12, "day12"), (14, "day14"), (17, "day17")] {
+            let export = exporter(day).unwrap();
+            let input = fs::read(format!("inputs-sample/{}", sample)).unwrap();
+            for ext in ["png", "gif"] {
+                let path = dir.join(format!("{}.{}", sample, ext));
+                export(&mut input.as_slice(), &options, &path).unwrap();
+                assert!(fs::metadata(&path).unwrap().len() > 0, "{}", path.display());
+            }
+        }
+
+        let png = fs::read(dir.join("day12.png")).unwrap();
+        let info = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        assert_eq!((info.info().width, info.info().height), (8, 5));
+        assert!(exporter(1).is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
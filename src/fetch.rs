@@ -0,0 +1,169 @@
+use crate::error::Result;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The real puzzle site, used unless `AOC_BASE_URL` points somewhere else.
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+/// File the session token is read from when `AOC_SESSION` isn't set.
+pub const SESSION_FILE: &str = ".session";
+
+const YEAR: u32 = 2022;
+const USER_AGENT: &str = "github.com/anall/aoc-rust-2022 by anall@andreanall.com";
+
+/// Somewhere puzzle inputs can be downloaded from.
+pub trait Fetcher {
+    fn fetch_input(&self, day: u32) -> Result<String>;
+}
+
+/// Talks to the puzzle site, or a stand-in for it, as a logged in user.
+pub struct HttpClient {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl HttpClient {
+    #[must_use]
+    pub fn new(base_url: &str, session: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        }
+    }
+
+    /// Uses `AOC_BASE_URL` and the session token from `AOC_SESSION` or [`SESSION_FILE`].
+    pub fn from_env() -> Result<Self> {
+        let base_url = env::var("AOC_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        let session = match env::var("AOC_SESSION") {
+            Ok(session) => session,
+            Err(_) => fs::read_to_string(SESSION_FILE).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "no AOC_SESSION set and couldn't read {}: {}",
+                        SESSION_FILE, e
+                    ),
+                )
+            })?,
+        };
+
+        Ok(Self::new(&base_url, session.trim()))
+    }
+
+    /// The address of a page for `day`, such as `input` or `answer`.
+    #[must_use]
+    pub fn day_url(&self, day: u32, page: &str) -> String {
+        format!("{}/{}/day/{}/{}", self.base_url, YEAR, day, page)
+    }
+
+    pub fn get(&self, url: &str) -> Result<String> {
+        Ok(self
+            .agent
+            .get(url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call()?
+            .into_string()?)
+    }
+
+    pub fn post_form(&self, url: &str, form: &[(&str, &str)]) -> Result<String> {
+        Ok(self
+            .agent
+            .post(url)
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(form)?
+            .into_string()?)
+    }
+}
+
+impl Fetcher for HttpClient {
+    fn fetch_input(&self, day: u32) -> Result<String> {
+        self.get(&self.day_url(day, "input"))
+    }
+}
+
+/// Puzzle inputs kept on disk, so each one is downloaded at most once.
+pub struct InputCache<F> {
+    dir: PathBuf,
+    fetcher: F,
+}
+
+impl<F: Fetcher> InputCache<F> {
+    pub fn new(dir: impl Into<PathBuf>, fetcher: F) -> Self {
+        Self {
+            dir: dir.into(),
+            fetcher,
+        }
+    }
+
+    /// Where `day`'s input is cached; the same file `InputSource::for_day` points at.
+    #[must_use]
+    pub fn path(&self, day: u32) -> PathBuf {
+        self.dir.join(format!("day{}", day))
+    }
+
+    #[must_use]
+    pub fn is_cached(&self, day: u32) -> bool {
+        self.path(day).exists()
+    }
+
+    /// Returns the path of `day`'s input, downloading it first if it isn't cached yet.
+    pub fn get(&self, day: u32) -> Result<PathBuf> {
+        let path = self.path(day);
+        if !path.exists() {
+            let input = self.fetcher.fetch_input(day)?;
+            fs::create_dir_all(&self.dir)?;
+            write_atomically(&path, &input)?;
+        }
+        Ok(path)
+    }
+}
+
+/// Writes through a temporary file so an interrupted download never looks like a cached input.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HttpClient, InputCache};
+    use crate::{error::Error, mock_server::MockServer};
+    use std::{env, fs, process};
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = env::temp_dir().join(format!("aoc2022-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn fetches_once() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/2022/day/1/input" => (200, "1000\n2000\n".to_string()),
+            _ => (404, "Not Found".to_string()),
+        });
+        let dir = scratch_dir("fetches-once");
+        let cache = InputCache::new(&dir, HttpClient::new(server.base_url(), "cafe"));
+
+        assert!(!cache.is_cached(1));
+        let path = cache.get(1).unwrap();
+        assert_eq!(path, dir.join("day1"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1000\n2000\n");
+        assert_eq!(cache.get(1).unwrap(), path);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].header("Cookie"), Some("session=cafe"));
+
+        assert!(matches!(cache.get(2), Err(Error::Http(_))));
+        assert!(!cache.is_cached(2));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
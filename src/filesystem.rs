@@ -0,0 +1,519 @@
+use std::{cell::OnceCell, cmp::Reverse, collections::BTreeMap, fmt::Write, iter};
+
+/// Index of a file or directory in a [`Filesystem`].
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    File(usize),
+    /// Contents by name, so they come out sorted.
+    Directory(BTreeMap<String, NodeId>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    name: String,
+    parent: Option<NodeId>,
+    entry: Entry,
+}
+
+/// A tree of files and directories, stored flat with every node pointing back to its parent.
+///
+/// Nodes are only ever added after their parent, so the total sizes can all be worked out in a
+/// single pass from the last node back to the root. They're worked out the first time one is
+/// asked for and kept until the tree next changes. None of them can overflow, since files are
+/// only added while the size of everything still fits in a `usize`.
+#[derive(Debug, Clone)]
+pub struct Filesystem {
+    nodes: Vec<Node>,
+    totals: OnceCell<Vec<usize>>,
+    used: usize,
+}
+
+impl Default for Filesystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Filesystem {
+    /// The root directory, which every filesystem starts out with.
+    pub const ROOT: NodeId = 0;
+
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: String::new(),
+                parent: None,
+                entry: Entry::Directory(BTreeMap::new()),
+            }],
+            totals: OnceCell::new(),
+            used: 0,
+        }
+    }
+
+    fn contents(&self, dir: NodeId) -> Option<&BTreeMap<String, NodeId>> {
+        match &self.nodes[dir].entry {
+            Entry::Directory(contents) => Some(contents),
+            Entry::File(_) => None,
+        }
+    }
+
+    fn insert(&mut self, dir: NodeId, name: &str, entry: Entry) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(dir),
+            entry,
+        });
+        match &mut self.nodes[dir].entry {
+            Entry::Directory(contents) => contents.insert(name.to_string(), id),
+            Entry::File(_) => unreachable!("only directories are added to"),
+        };
+        id
+    }
+
+    /// Adds a directory called `name` to `dir`, or finds the one already there. `None` if `dir`
+    /// is a file, or there's a file called `name` in it.
+    pub fn add_directory(&mut self, dir: NodeId, name: &str) -> Option<NodeId> {
+        if let Some(existing) = self.contents(dir)?.get(name).copied() {
+            return self.is_directory(existing).then_some(existing);
+        }
+        self.totals.take();
+        Some(self.insert(dir, name, Entry::Directory(BTreeMap::new())))
+    }
+
+    /// Adds a file called `name` to `dir`, or updates the size of the one already there. `None`
+    /// if `dir` is a file, there's a directory called `name` in it, or the size of everything
+    /// would no longer fit in a `usize`.
+    pub fn add_file(&mut self, dir: NodeId, name: &str, size: usize) -> Option<NodeId> {
+        let existing = self.contents(dir)?.get(name).copied();
+        let old_size = match existing.map(|existing| &self.nodes[existing].entry) {
+            Some(Entry::File(old_size)) => *old_size,
+            Some(Entry::Directory(_)) => return None,
+            None => 0,
+        };
+        self.used = (self.used - old_size).checked_add(size)?;
+
+        let id = match existing {
+            Some(existing) => {
+                self.nodes[existing].entry = Entry::File(size);
+                existing
+            }
+            None => self.insert(dir, name, Entry::File(size)),
+        };
+        self.totals.take();
+        Some(id)
+    }
+
+    /// Total size of every file, the same as the size of the root.
+    #[must_use]
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Number of files and directories, including the root.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether there's nothing but the root directory.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    #[must_use]
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.nodes[id].name
+    }
+
+    #[must_use]
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    #[must_use]
+    pub fn is_directory(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].entry, Entry::Directory(_))
+    }
+
+    /// The file or directory called `name` directly inside `dir`.
+    #[must_use]
+    pub fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        self.contents(dir)?.get(name).copied()
+    }
+
+    /// What `dir` holds, sorted by name; nothing for a file.
+    pub fn children(&self, dir: NodeId) -> impl DoubleEndedIterator<Item = NodeId> + '_ {
+        self.contents(dir)
+            .into_iter()
+            .flat_map(|c| c.values().copied())
+    }
+
+    /// Size of a file, or the total size of everything inside a directory.
+    #[must_use]
+    pub fn size(&self, id: NodeId) -> usize {
+        self.totals.get_or_init(|| {
+            let mut totals: Vec<usize> = self
+                .nodes
+                .iter()
+                .map(|node| match node.entry {
+                    Entry::File(size) => size,
+                    Entry::Directory(_) => 0,
+                })
+                .collect();
+            for (id, node) in self.nodes.iter().enumerate().rev() {
+                if let Some(parent) = node.parent {
+                    totals[parent] += totals[id];
+                }
+            }
+            totals
+        })[id]
+    }
+
+    /// Absolute path to `id`, `/` for the root.
+    #[must_use]
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut cur = id;
+        while let Some(parent) = self.nodes[cur].parent {
+            names.push(self.name(cur));
+            cur = parent;
+        }
+        if names.is_empty() {
+            return "/".to_string();
+        }
+        names.iter().rev().fold(String::new(), |mut path, name| {
+            path.push('/');
+            path.push_str(name);
+            path
+        })
+    }
+
+    /// Follows `path` from `from`, or from the root if it starts with `/`. `None` if it climbs
+    /// above the root or goes through anything that isn't there.
+    #[must_use]
+    pub fn resolve(&self, from: NodeId, path: &str) -> Option<NodeId> {
+        let mut cur = if path.starts_with('/') {
+            Self::ROOT
+        } else {
+            from
+        };
+        for component in path.split('/') {
+            cur = match component {
+                "" | "." => cur,
+                ".." => self.parent(cur)?,
+                name => self.child(cur, name)?,
+            };
+        }
+        Some(cur)
+    }
+
+    /// The node at absolute `path`.
+    #[must_use]
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        self.resolve(Self::ROOT, path)
+    }
+
+    /// Every directory, the root first and each before anything inside it.
+    pub fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len()).filter(|&id| self.is_directory(id))
+    }
+
+    /// The path and total size of every directory, in the order of [`directories`](Self::directories).
+    pub fn directory_sizes(&self) -> impl Iterator<Item = (String, usize)> + '_ {
+        self.directories().map(|id| (self.path(id), self.size(id)))
+    }
+
+    /// `dir` and everything under it, each directory followed by its contents in name order, along
+    /// with how far below `dir` they are.
+    pub fn walk(&self, dir: NodeId) -> impl Iterator<Item = (NodeId, usize)> + '_ {
+        let mut stack = vec![(dir, 0)];
+        iter::from_fn(move || {
+            let (id, depth) = stack.pop()?;
+            stack.extend(self.children(id).rev().map(|child| (child, depth + 1)));
+            Some((id, depth))
+        })
+    }
+
+    /// Everything under `dir` drawn the way the puzzle does, with the size of each directory too.
+    #[must_use]
+    pub fn render_tree(&self, dir: NodeId) -> String {
+        let mut out = String::new();
+        for (id, depth) in self.walk(dir) {
+            let name = if id == Self::ROOT { "/" } else { self.name(id) };
+            let kind = if self.is_directory(id) { "dir" } else { "file" };
+            writeln!(
+                out,
+                "{:indent$}- {} ({}, size={})",
+                "",
+                name,
+                kind,
+                self.size(id),
+                indent = depth * 2
+            )
+            .expect("writing to a String");
+        }
+        out
+    }
+
+    /// The directories `du -d max_depth` would list: those at most `max_depth` below `dir`, or all
+    /// of them for `None`, each after the ones inside it.
+    #[must_use]
+    pub fn du(&self, dir: NodeId, max_depth: Option<usize>) -> Vec<NodeId> {
+        let mut out = Vec::new();
+        let mut stack = vec![(dir, 0, false)];
+        while let Some((id, depth, expanded)) = stack.pop() {
+            if expanded {
+                out.push(id);
+                continue;
+            }
+            stack.push((id, depth, true));
+            if max_depth.is_none_or(|max_depth| depth < max_depth) {
+                let subdirs = self.children(id).rev().filter(|&c| self.is_directory(c));
+                stack.extend(subdirs.map(|child| (child, depth + 1, false)));
+            }
+        }
+        out
+    }
+
+    /// Everything under `dir`, including itself, that matches `query`, in the order of
+    /// [`walk`](Self::walk).
+    #[must_use]
+    pub fn find(&self, dir: NodeId, query: &Query) -> Vec<NodeId> {
+        self.walk(dir)
+            .map(|(id, _)| id)
+            .filter(|&id| query.matches(self, id))
+            .collect()
+    }
+
+    /// Every file under `dir`, largest first.
+    #[must_use]
+    pub fn files_by_size(&self, dir: NodeId) -> Vec<NodeId> {
+        let mut files: Vec<NodeId> = self
+            .walk(dir)
+            .map(|(id, _)| id)
+            .filter(|&id| !self.is_directory(id))
+            .collect();
+        files.sort_by_key(|&id| Reverse(self.size(id)));
+        files
+    }
+}
+
+/// Whether [`Query`] wants files or directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    File,
+    Directory,
+}
+
+/// What [`Filesystem::find`] looks for; everything, if left at the default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    /// A shell pattern the name has to match, where `*` stands for any run of characters and `?`
+    /// for any one.
+    pub name: Option<String>,
+    pub kind: Option<Kind>,
+    /// Smallest size to include, counting everything inside a directory.
+    pub min_size: Option<usize>,
+    /// Largest size to include, counting everything inside a directory.
+    pub max_size: Option<usize>,
+}
+
+impl Query {
+    fn matches(&self, fs: &Filesystem, id: NodeId) -> bool {
+        let kind = if fs.is_directory(id) {
+            Kind::Directory
+        } else {
+            Kind::File
+        };
+        let size = fs.size(id);
+
+        self.name
+            .as_ref()
+            .is_none_or(|pattern| glob_match(pattern, fs.name(id)))
+            && self.kind.is_none_or(|wanted| wanted == kind)
+            && self.min_size.is_none_or(|min_size| size >= min_size)
+            && self.max_size.is_none_or(|max_size| size <= max_size)
+    }
+}
+
+/// Whether `name` matches the shell pattern `pattern`, in which `*` stands for any run of
+/// characters and `?` for any one.
+#[must_use]
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // where the last `*` was, and how much of the name it has swallowed so far
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::{glob_match, Filesystem, Kind, Query};
+
+    fn sample() -> Filesystem {
+        let mut fs = Filesystem::new();
+        let a = fs.add_directory(Filesystem::ROOT, "a").unwrap();
+        let e = fs.add_directory(a, "e").unwrap();
+        fs.add_file(e, "i", 584).unwrap();
+        fs.add_file(a, "f", 29116).unwrap();
+        fs.add_file(Filesystem::ROOT, "b.txt", 14_848_514).unwrap();
+        fs
+    }
+
+    #[test]
+    fn sizes() {
+        let mut fs = sample();
+        assert_eq!(
+            fs.directory_sizes().collect::<Vec<_>>(),
+            [
+                ("/".to_string(), 14_878_214),
+                ("/a".to_string(), 29700),
+                ("/a/e".to_string(), 584),
+            ]
+        );
+
+        // changing the tree forgets the old totals
+        let e = fs.lookup("/a/e").unwrap();
+        fs.add_file(e, "i", 1000).unwrap();
+        fs.add_file(e, "j", 1).unwrap();
+        assert_eq!(fs.size(e), 1001);
+        assert_eq!(fs.size(Filesystem::ROOT), 14_878_631);
+    }
+
+    #[test]
+    fn paths() {
+        let fs = sample();
+        let e = fs.lookup("/a/e").unwrap();
+        assert_eq!(fs.path(e), "/a/e");
+        assert_eq!(fs.name(e), "e");
+        assert_eq!(fs.path(Filesystem::ROOT), "/");
+        assert_eq!(fs.resolve(e, "../../a/./e/i"), fs.lookup("a/e/i"));
+        assert_eq!(fs.resolve(e, "/"), Some(Filesystem::ROOT));
+        assert_eq!(fs.resolve(e, "../../.."), None);
+        assert_eq!(fs.lookup("/b.txt/x"), None);
+        assert_eq!(fs.lookup("/nope"), None);
+
+        let a = fs.lookup("/a").unwrap();
+        let names: Vec<&str> = fs.children(a).map(|id| fs.name(id)).collect();
+        assert_eq!(names, ["e", "f"]);
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut fs = sample();
+        let a = fs.lookup("/a").unwrap();
+        assert_eq!(fs.add_directory(Filesystem::ROOT, "a"), Some(a));
+        assert_eq!(fs.add_directory(a, "f"), None);
+        assert_eq!(fs.add_file(a, "e", 1), None);
+        let b = fs.lookup("/b.txt").unwrap();
+        assert_eq!(fs.add_file(b, "c", 1), None);
+        assert_eq!(fs.len(), 6);
+
+        let used = fs.used();
+        assert_eq!(fs.add_file(a, "g", usize::MAX), None, "too big to add up");
+        assert_eq!(fs.used(), used);
+        let f = fs.lookup("/a/f").unwrap();
+        let room = usize::MAX - used + fs.size(f);
+        assert_eq!(fs.add_file(a, "f", room), Some(f), "replaces the old size");
+        assert_eq!(fs.size(Filesystem::ROOT), usize::MAX);
+    }
+
+    #[test]
+    fn deep() {
+        let mut fs = Filesystem::new();
+        let mut dir = Filesystem::ROOT;
+        for _ in 0..100_000 {
+            dir = fs.add_directory(dir, "d").unwrap();
+            fs.add_file(dir, "f", 1).unwrap();
+        }
+        assert_eq!(fs.size(Filesystem::ROOT), 100_000);
+        assert_eq!(fs.size(dir), 1);
+    }
+
+    #[test]
+    fn reports() {
+        let fs = sample();
+        assert_eq!(
+            fs.render_tree(Filesystem::ROOT).lines().collect::<Vec<_>>(),
+            [
+                "- / (dir, size=14878214)",
+                "  - a (dir, size=29700)",
+                "    - e (dir, size=584)",
+                "      - i (file, size=584)",
+                "    - f (file, size=29116)",
+                "  - b.txt (file, size=14848514)",
+            ]
+        );
+
+        let paths =
+            |ids: Vec<usize>| -> Vec<String> { ids.iter().map(|&id| fs.path(id)).collect() };
+        assert_eq!(paths(fs.du(Filesystem::ROOT, None)), ["/a/e", "/a", "/"]);
+        assert_eq!(paths(fs.du(Filesystem::ROOT, Some(1))), ["/a", "/"]);
+        assert_eq!(paths(fs.du(Filesystem::ROOT, Some(0))), ["/"]);
+        assert_eq!(
+            paths(fs.files_by_size(Filesystem::ROOT)),
+            ["/b.txt", "/a/f", "/a/e/i"]
+        );
+
+        let large_dirs = Query {
+            kind: Some(Kind::Directory),
+            min_size: Some(1000),
+            ..Query::default()
+        };
+        assert_eq!(paths(fs.find(Filesystem::ROOT, &large_dirs)), ["/", "/a"]);
+        let small = Query {
+            max_size: Some(1000),
+            ..Query::default()
+        };
+        assert_eq!(paths(fs.find(Filesystem::ROOT, &small)), ["/a/e", "/a/e/i"]);
+        let named = Query {
+            name: Some("*.txt".to_string()),
+            ..Query::default()
+        };
+        assert_eq!(paths(fs.find(Filesystem::ROOT, &named)), ["/b.txt"]);
+        let a = fs.lookup("/a").unwrap();
+        assert_eq!(
+            paths(fs.find(a, &Query::default())),
+            ["/a", "/a/e", "/a/e/i", "/a/f"]
+        );
+    }
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("*.txt", "b.txt"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("?.t?t", "b.txt"));
+        assert!(!glob_match("*.txt", "b.txt.bak"));
+        assert!(!glob_match("a?", "a"));
+        assert!(!glob_match("", "a"));
+    }
+}
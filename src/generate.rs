@@ -0,0 +1,144 @@
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day17, day2, day25, day3, day4, day5, day6,
+    day7, day8, day9, solution::Solution,
+};
+use rand::SeedableRng;
+
+/// The random number generator handed to generators; `ChaCha` rather than `StdRng` so that a seed
+/// keeps producing the same input whatever version of `rand` gets built.
+pub type Random = rand_chacha::ChaCha8Rng;
+
+/// Which input to generate and how large.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Options {
+    pub seed: u64,
+    /// What this counts depends on the day; `None` picks something close to a real input.
+    pub size: Option<usize>,
+}
+
+/// A day that can make up random puzzle inputs, for stress testing.
+pub trait Generate: Solution {
+    /// Size of a typical real input.
+    const SIZE: usize;
+
+    /// Makes up an input of roughly `size`, which both parts can solve with `params`.
+    fn generate(rng: &mut Random, size: usize, params: &Self::Params) -> String;
+}
+
+/// Puts `lines` together as an input file, ending in a newline like the real ones.
+#[must_use]
+pub fn join_lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().fold(String::new(), |mut text, line| {
+        text.push_str(&line);
+        text.push('\n');
+        text
+    })
+}
+
+/// Generates an input.
+pub type Generator = fn(&Options) -> String;
+
+/// Generates an input for the day's default parameters.
+#[must_use]
+pub fn generate<S: Generate>(options: &Options) -> String {
+    let mut rng = Random::seed_from_u64(options.seed);
+    S::generate(
+        &mut rng,
+        options.size.unwrap_or(S::SIZE),
+        &S::Params::default(),
+    )
+}
+
+/// Every day that can be generated, in order.
+pub const GENERATORS: &[(u32, Generator)] = &[
+    (1, generate::<day1::Day1>),
+    (2, generate::<day2::Day2>),
+    (3, generate::<day3::Day3>),
+    (4, generate::<day4::Day4>),
+    (5, generate::<day5::Day5>),
+    (6, generate::<day6::Day6>),
+    (7, generate::<day7::Day7>),
+    (8, generate::<day8::Day8>),
+    (9, generate::<day9::Day9>),
+    (10, generate::<day10::Day10>),
+    (11, generate::<day11::Day11>),
+    (12, generate::<day12::Day12>),
+    (13, generate::<day13::Day13>),
+    (14, generate::<day14::Day14>),
+    (15, generate::<day15::Day15>),
+    (17, generate::<day17::Day17>),
+    (25, generate::<day25::Day25>),
+];
+
+#[must_use]
+pub fn generator(day: u32) -> Option<Generator> {
+    GENERATORS
+        .iter()
+        .find(|(registered, _)| *registered == day)
+        .map(|(_, generate)| *generate)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{generator, Generate, Options, Random};
+    use crate::{
+        day1, day10, day11, day12, day13, day14, day15, day17, day2, day25, day3, day4, day5, day6,
+        day7, day8, day9, solution::solve_with,
+    };
+    use rand::SeedableRng;
+
+    /// Solves a few small inputs and one of the usual size, none of which may be rejected.
+    fn solves<S: Generate>(params: &S::Params) {
+        for (seed, size) in [(0, 1), (1, 5), (2, 20), (3, S::SIZE)] {
+            let input = S::generate(&mut Random::seed_from_u64(seed), size, params);
+            if let Err(e) = solve_with::<S, _>(input.as_bytes(), params) {
+                panic!(
+                    "day {} seed {} size {}: {}\n{}",
+                    S::DAY,
+                    seed,
+                    size,
+                    e,
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn solvable() {
+        solves::<day1::Day1>(&());
+        solves::<day2::Day2>(&());
+        solves::<day3::Day3>(&());
+        solves::<day4::Day4>(&());
+        solves::<day5::Day5>(&());
+        solves::<day6::Day6>(&());
+        solves::<day7::Day7>(&day7::Params::default());
+        solves::<day8::Day8>(&());
+        solves::<day9::Day9>(&());
+        solves::<day10::Day10>(&());
+        solves::<day11::Day11>(&());
+        solves::<day12::Day12>(&());
+        solves::<day13::Day13>(&());
+        solves::<day14::Day14>(&());
+        // the real search area takes too long to scan in a debug build
+        solves::<day15::Day15>(&day15::Params {
+            part1_y: 10,
+            part2_extent: 20,
+        });
+        solves::<day17::Day17>(&());
+        solves::<day25::Day25>(&());
+    }
+
+    #[test]
+    fn seeded() {
+        let generate = generator(7).unwrap();
+        let options = |seed| Options {
+            seed,
+            size: Some(10),
+        };
+
+        assert_eq!(generate(&options(1)), generate(&options(1)));
+        assert_ne!(generate(&options(1)), generate(&options(2)));
+        assert!(generator(16).is_none());
+    }
+}
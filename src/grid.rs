@@ -0,0 +1,332 @@
+use crate::{error::Result, input::NumberedLines};
+use adventlib::aoc::point2d::Point2D;
+use std::{
+    fmt,
+    io::BufRead,
+    ops::{Index, IndexMut},
+};
+
+/// A `(row, column)` position in a [`Grid`].
+pub type Pos = (usize, usize);
+
+/// Up, left, right and down, as `(row, column)` steps.
+pub const NEIGHBOURS4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+/// The four orthogonal steps plus the four diagonals, in reading order.
+pub const NEIGHBOURS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid, stored row-major in a single `Vec`.
+///
+/// Cells are addressed by `(row, column)`, or by a [`Point2D`] whose x is the column and y the row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    #[must_use]
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from rows, which must all be the same length.
+    #[must_use]
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+
+        Some(Self {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Reads a character map up to a blank line or the end of the input, converting each
+    /// character with `cell`; a `None` from `cell` or a ragged row is reported against its line.
+    pub fn parse<R: BufRead>(
+        lines: &mut NumberedLines<R>,
+        expected: &str,
+        mut cell: impl FnMut(Pos, char) -> Option<T>,
+    ) -> Result<Self> {
+        let mut grid = Self {
+            width: 0,
+            height: 0,
+            cells: Vec::new(),
+        };
+
+        for line in lines {
+            let line = line?;
+            if line.text.is_empty() {
+                break;
+            }
+
+            let row = grid.height;
+            let start = grid.cells.len();
+            for (col, ch) in line.text.chars().enumerate() {
+                match cell((row, col), ch) {
+                    Some(value) => grid.cells.push(value),
+                    None => return Err(line.error(expected).into()),
+                }
+            }
+
+            let width = grid.cells.len() - start;
+            if row == 0 {
+                grid.width = width;
+            } else if width != grid.width {
+                return Err(line
+                    .error(format!("{} {} wide", expected, grid.width))
+                    .into());
+            }
+            grid.height += 1;
+        }
+
+        Ok(grid)
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn contains(&self, (row, col): Pos) -> bool {
+        row < self.height && col < self.width
+    }
+
+    #[must_use]
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.0 * self.width + pos.1])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// The position `point` refers to, if it is inside the grid.
+    #[must_use]
+    pub fn pos_of(&self, point: Point2D) -> Option<Pos> {
+        let pos = (
+            usize::try_from(point.1).ok()?,
+            usize::try_from(point.0).ok()?,
+        );
+        self.contains(pos).then_some(pos)
+    }
+
+    /// Takes one `(row, column)` step from `pos`, if that stays inside the grid.
+    #[must_use]
+    pub fn step(&self, (row, col): Pos, (d_row, d_col): (isize, isize)) -> Option<Pos> {
+        let pos = (
+            row.checked_add_signed(d_row)?,
+            col.checked_add_signed(d_col)?,
+        );
+        self.contains(pos).then_some(pos)
+    }
+
+    /// Every position, in reading order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// Every cell, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    #[must_use]
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks_exact would panic on a zero width grid
+        (0..self.height).map(move |row| self.row(row))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "column {} out of bounds", col);
+        self.cells[col..].iter().step_by(self.width)
+    }
+
+    /// The up to four orthogonally adjacent positions.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS4
+            .iter()
+            .filter_map(move |&delta| self.step(pos, delta))
+    }
+
+    /// The up to eight adjacent positions, diagonals included.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS8
+            .iter()
+            .filter_map(move |&delta| self.step(pos, delta))
+    }
+
+    /// Walks from `pos` in steps of `delta` until the edge, not including `pos` itself.
+    pub fn ray(&self, pos: Pos, delta: (isize, isize)) -> impl Iterator<Item = (Pos, &T)> {
+        let mut current = pos;
+        std::iter::from_fn(move || {
+            current = self.step(current, delta)?;
+            Some((current, &self[current]))
+        })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        let (width, height) = (self.width, self.height);
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{:?} out of bounds for a {}x{} grid", pos, width, height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} out of bounds for a {}x{} grid", pos, width, height))
+    }
+}
+
+impl<T> Index<Point2D> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point2D) -> &T {
+        match self.pos_of(point) {
+            Some(pos) => &self[pos],
+            None => panic!(
+                "{:?} out of bounds for a {}x{} grid",
+                point, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Point2D> for Grid<T> {
+    fn index_mut(&mut self, point: Point2D) -> &mut T {
+        match self.pos_of(point) {
+            Some(pos) => &mut self[pos],
+            None => panic!(
+                "{:?} out of bounds for a {}x{} grid",
+                point, self.width, self.height
+            ),
+        }
+    }
+}
+
+/// Draws each cell with its own `Display`, one line per row.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Grid, NEIGHBOURS4};
+    use crate::{error::Error, input::NumberedLines};
+    use adventlib::aoc::point2d::Point2D;
+
+    fn digits(map: &str) -> crate::error::Result<Grid<u32>> {
+        Grid::parse(
+            &mut NumberedLines::new(0, map.as_bytes()),
+            "digits",
+            |_, ch| ch.to_digit(10),
+        )
+    }
+
+    #[test]
+    fn parse_and_index() {
+        let grid = digits("123\n456\n\n789\n").unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid[Point2D(2, 0)], 3);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.pos_of(Point2D(-1, 0)), None);
+        assert_eq!(grid.to_string(), "123\n456");
+
+        for (map, line) in [("123\n4x6\n", 2), ("123\n45\n", 2)] {
+            match digits(map) {
+                Err(Error::Parse(e)) => assert_eq!(e.line, line),
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn iterators() {
+        let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]).unwrap();
+
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![3, 6, 9]);
+        assert_eq!(grid.rows().count(), 3);
+        assert_eq!(grid.positions().nth(4), Some((1, 1)));
+
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbours8((2, 2)).count(), 3);
+
+        let right = NEIGHBOURS4[2];
+        assert_eq!(
+            grid.ray((1, 0), right).map(|(_, v)| *v).collect::<Vec<_>>(),
+            vec![5, 6]
+        );
+        assert_eq!(grid.ray((1, 2), right).count(), 0);
+
+        assert_eq!(grid.map(|v| v % 2 == 0).iter().filter(|v| **v).count(), 4);
+        assert!(Grid::from_rows(vec![vec![1], vec![2, 3]]).is_none());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::error::{ParseError, Result};
+use adventlib::aoc;
+use std::{
+    env,
+    io::{self, BufRead},
+    path::PathBuf,
+};
+
+/// Directory holding the real puzzle inputs unless `AOC_INPUT_DIR` says otherwise.
+pub const DEFAULT_INPUT_DIR: &str = "inputs";
+
+/// The directory real puzzle inputs live in: `AOC_INPUT_DIR` or [`DEFAULT_INPUT_DIR`].
+#[must_use]
+pub fn input_dir() -> PathBuf {
+    env::var_os("AOC_INPUT_DIR").map_or_else(|| DEFAULT_INPUT_DIR.into(), PathBuf::from)
+}
+
+/// Where a day's puzzle input is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Stdin,
+    Path(PathBuf),
+}
+
+impl InputSource {
+    /// Interprets a command line argument: `-` reads stdin, anything else is a path.
+    #[must_use]
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            Self::Stdin
+        } else {
+            Self::Path(PathBuf::from(arg))
+        }
+    }
+
+    /// The usual input file for `day`, looked up in [`input_dir`].
+    #[must_use]
+    pub fn for_day(day: u32) -> Self {
+        Self::Path(input_dir().join(format!("day{}", day)))
+    }
+
+    pub fn open(&self) -> aoc::Result<Box<dyn BufRead>> {
+        match self {
+            Self::Stdin => Ok(Box::new(io::stdin().lock())),
+            Self::Path(path) => Ok(Box::new(aoc::file(&path.to_string_lossy())?)),
+        }
+    }
+}
+
+/// One line of a day's input, remembering where it came from so it can be complained about.
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub day: u32,
+    pub number: usize,
+    pub text: String,
+}
+
+impl Line {
+    #[must_use]
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            day: self.day,
+            line: self.number,
+            text: Some(self.text.clone()),
+            expected: expected.into(),
+        }
+    }
+}
+
+/// Reads a day's input line by line, numbering the lines from 1.
+pub struct NumberedLines<R> {
+    day: u32,
+    lines: io::Lines<R>,
+    number: usize,
+}
+
+impl<R: BufRead> NumberedLines<R> {
+    pub fn new(day: u32, reader: R) -> Self {
+        Self {
+            day,
+            lines: reader.lines(),
+            number: 0,
+        }
+    }
+
+    /// The error for input that ran out while `expected` was still wanted.
+    #[must_use]
+    pub fn end_of_input(&self, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            day: self.day,
+            line: self.number + 1,
+            text: None,
+            expected: expected.into(),
+        }
+    }
+
+    /// The next line, which has to be there.
+    pub fn expect_next(&mut self, expected: impl Into<String>) -> Result<Line> {
+        match self.next() {
+            Some(line) => line,
+            None => Err(self.end_of_input(expected).into()),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for NumberedLines<R> {
+    type Item = Result<Line>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = match self.lines.next()? {
+            Ok(text) => text,
+            Err(e) => return Some(Err(e.into())),
+        };
+        self.number += 1;
+
+        Some(Ok(Line {
+            day: self.day,
+            number: self.number,
+            text,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{InputSource, NumberedLines};
+    use crate::error::Error;
+    use std::path::PathBuf;
+
+    #[test]
+    fn from_arg() {
+        assert_eq!(InputSource::from_arg("-"), InputSource::Stdin);
+        assert_eq!(
+            InputSource::from_arg("inputs-sample/day1"),
+            InputSource::Path(PathBuf::from("inputs-sample/day1"))
+        );
+    }
+
+    #[test]
+    fn numbered_lines() {
+        let mut lines = NumberedLines::new(3, "first\nsecond\n".as_bytes());
+
+        let first = lines.next().unwrap().unwrap();
+        assert_eq!((first.number, first.text.as_str()), (1, "first"));
+        assert_eq!(first.error("x").line, 1);
+
+        lines.expect_next("a second line").unwrap();
+        match lines.expect_next("a third line") {
+            Err(Error::Parse(e)) => assert_eq!((e.day, e.line, e.text), (3, 3, None)),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use std::cmp;
+
+/// A set of integers stored as sorted, disjoint, inclusive ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntSpan {
+    ranges: Vec<(i64, i64)>,
+}
+impl IntSpan {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// The set holding just `min..=max`, or nothing if `min > max`.
+    #[must_use]
+    pub fn with_range(min: i64, max: i64) -> Self {
+        let mut span = Self::new();
+        span.add_range(min, max);
+        span
+    }
+
+    /// Adds the inclusive range `min..=max`, merging it with any ranges it touches.
+    pub fn add_range(&mut self, min: i64, max: i64) {
+        if min > max {
+            return;
+        }
+
+        if self.ranges.is_empty() {
+            self.ranges.push((min, max));
+        } else {
+            // find insertion point
+            let insert_pos = match self.ranges.binary_search_by(|v| v.0.cmp(&min)) {
+                Ok(v) | Err(v) => v,
+            };
+            self.ranges.insert(insert_pos, (min, max));
+            // now fixup
+            let mut cur_i = insert_pos;
+            while cur_i < self.ranges.len() {
+                // did we just encroach on the next element? (saturating, as a range can end at
+                // i64::MAX)
+                if cur_i + 1 < self.ranges.len()
+                    && self.ranges[cur_i + 1].0 <= self.ranges[cur_i].1.saturating_add(1)
+                {
+                    // extend ourselves and delete the next element
+                    self.ranges[cur_i].1 = cmp::max(self.ranges[cur_i].1, self.ranges[cur_i + 1].1);
+                    self.ranges.remove(cur_i + 1);
+                } else if cur_i > 0
+                    && self.ranges[cur_i - 1].1.saturating_add(1) >= self.ranges[cur_i].0
+                {
+                    // extend previous element and delete ourselves
+                    self.ranges[cur_i - 1].1 =
+                        cmp::max(self.ranges[cur_i - 1].1, self.ranges[cur_i].1);
+                    self.ranges.remove(cur_i);
+                } else {
+                    cur_i += 1;
+                }
+            }
+        }
+    }
+
+    pub fn insert(&mut self, value: i64) {
+        self.add_range(value, value);
+    }
+
+    /// Removes the inclusive range `min..=max`, splitting any range it falls inside.
+    pub fn remove_range(&mut self, min: i64, max: i64) {
+        if min > max {
+            return;
+        }
+
+        let mut ranges = Vec::with_capacity(self.ranges.len() + 1);
+        for &(lo, hi) in &self.ranges {
+            if hi < min || lo > max {
+                ranges.push((lo, hi));
+            } else {
+                if lo < min {
+                    ranges.push((lo, min - 1));
+                }
+                if hi > max {
+                    ranges.push((max + 1, hi));
+                }
+            }
+        }
+        self.ranges = ranges;
+    }
+
+    pub fn remove(&mut self, value: i64) {
+        self.remove_range(value, value);
+    }
+
+    /// The range holding `value`, if any.
+    fn range_of(&self, value: i64) -> Option<(i64, i64)> {
+        let i = self.ranges.partition_point(|range| range.1 < value);
+        self.ranges.get(i).copied().filter(|range| range.0 <= value)
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: i64) -> bool {
+        self.range_of(value).is_some()
+    }
+
+    /// Whether every integer in `min..=max` is in the set.
+    #[must_use]
+    pub fn contains_range(&self, min: i64, max: i64) -> bool {
+        min > max || self.range_of(min).is_some_and(|range| max <= range.1)
+    }
+
+    #[must_use]
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other
+            .ranges
+            .iter()
+            .all(|&(min, max)| self.contains_range(min, max))
+    }
+
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut span = self.clone();
+        for &(min, max) in &other.ranges {
+            span.add_range(min, max);
+        }
+        span
+    }
+
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let (min, max) = (cmp::max(a.0, b.0), cmp::min(a.1, b.1));
+            if min <= max {
+                ranges.push((min, max));
+            }
+            // whichever ends first can't overlap anything further on
+            if a.1 < b.1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Everything in `min..=max` that is not in the set.
+    #[must_use]
+    pub fn complement(&self, min: i64, max: i64) -> Self {
+        let mut span = Self::with_range(min, max);
+        for &(lo, hi) in &self.ranges {
+            span.remove_range(lo, hi);
+        }
+        span
+    }
+
+    /// The ranges missing between the first and last covered integers.
+    pub fn gaps(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.ranges.windows(2).map(|w| (w[0].1 + 1, w[1].0 - 1))
+    }
+
+    /// Number of integers covered, unlike [`len`](Self::len). Saturates at `u64::MAX`, which only
+    /// the whole of `i64` goes past.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.ranges.iter().fold(0, |total: u64, range| {
+            total
+                .saturating_add(range.1.abs_diff(range.0))
+                .saturating_add(1)
+        })
+    }
+
+    /// Number of disjoint ranges, not the number of integers covered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    #[must_use]
+    pub fn ranges(&self) -> &[(i64, i64)] {
+        &self.ranges
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::IntSpan;
+    use proptest::prelude::*;
+
+    /// Everything the spans are built from falls within `-LIMIT..LIMIT`.
+    const LIMIT: i64 = 64;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Op {
+        Add(i64, i64),
+        Remove(i64, i64),
+    }
+
+    fn ops() -> impl Strategy<Value = Vec<Op>> {
+        let bound = -LIMIT..LIMIT;
+        prop::collection::vec(
+            (any::<bool>(), bound.clone(), bound).prop_map(|(add, a, b)| {
+                if add {
+                    Op::Add(a, b)
+                } else {
+                    Op::Remove(a, b)
+                }
+            }),
+            0..20,
+        )
+    }
+
+    fn index(value: i64) -> usize {
+        usize::try_from(value + LIMIT).unwrap()
+    }
+
+    /// Applies `ops` both to a span and to one flag per integer in `-LIMIT..LIMIT`.
+    fn build(ops: &[Op]) -> (IntSpan, Vec<bool>) {
+        let mut span = IntSpan::new();
+        let mut bits = vec![false; index(LIMIT)];
+        for &op in ops {
+            let (min, max, set) = match op {
+                Op::Add(min, max) => {
+                    span.add_range(min, max);
+                    (min, max, true)
+                }
+                Op::Remove(min, max) => {
+                    span.remove_range(min, max);
+                    (min, max, false)
+                }
+            };
+            for v in min..=max {
+                bits[index(v)] = set;
+            }
+        }
+        (span, bits)
+    }
+
+    fn matches_bits(span: &IntSpan, bits: &[bool]) -> Result<(), TestCaseError> {
+        for (value, &bit) in (-LIMIT..LIMIT).zip(bits) {
+            prop_assert_eq!(span.contains(value), bit, "{}", value);
+        }
+        let count = bits.iter().filter(|&&bit| bit).count();
+        prop_assert_eq!(span.count(), u64::try_from(count).unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn intspan() {
+        let mut span = IntSpan::new();
+
+        assert_eq!(span.len(), 0);
+
+        // 5-10
+        span.add_range(5, 10);
+        assert_eq!(span.ranges, vec![(5, 10)]);
+
+        // 5-10, 20-30
+        span.add_range(20, 30);
+        assert_eq!(span.ranges, vec![(5, 10), (20, 30)]);
+
+        // 1-10, 20-30
+        span.add_range(1, 5);
+        assert_eq!(span.ranges, vec![(1, 10), (20, 30)]);
+
+        // 1-30 ( now let's try subsuming the entire range )
+        span.add_range(1, 21);
+        assert_eq!(span.ranges, vec![(1, 30)]);
+
+        // 1-50
+        span.add_range(29, 50);
+        assert_eq!(span.ranges, vec![(1, 50)]);
+
+        // 1-50,75-100
+        span.add_range(75, 100);
+        assert_eq!(span.ranges, vec![(1, 50), (75, 100)]);
+
+        // 1-50,60-70,75-100
+        span.add_range(60, 70);
+        assert_eq!(span.ranges, vec![(1, 50), (60, 70), (75, 100)]);
+
+        // 1-100
+        span.add_range(50, 75);
+        assert_eq!(span.ranges, vec![(1, 100)]);
+
+        // 1-200
+        span.add_range(101, 200);
+        assert_eq!(span.ranges, vec![(1, 200)]);
+
+        // (-200)-200
+        span.add_range(-200, 0);
+        assert_eq!(span.ranges, vec![(-200, 200)]);
+    }
+
+    #[test]
+    fn intspan_real() {
+        let mut span = IntSpan::new();
+
+        assert_eq!(span.len(), 0);
+
+        // (-12)-2
+        span.add_range(-12, 2);
+        assert_eq!(span.ranges, vec![(-12, 2)]);
+
+        // (-12)-2,5-13
+        span.add_range(5, 13);
+        assert_eq!(span.ranges, vec![(-12, 2), (5, 13)]);
+
+        // (-12)-13
+        span.add_range(-5, 13);
+        assert_eq!(span.ranges, vec![(-12, 13)]);
+
+        // (-12)-13
+        span.add_range(-3, 3);
+        assert_eq!(span.ranges, vec![(-12, 13)]);
+
+        // (-12)-25
+        span.add_range(9, 25);
+        assert_eq!(span.ranges, vec![(-12, 25)]);
+
+        // (-12)-25
+        span.add_range(7, 17);
+        assert_eq!(span.ranges, vec![(-12, 25)]);
+    }
+
+    #[test]
+    fn set_operations() {
+        let mut span = IntSpan::with_range(1, 10);
+
+        // 1-4,7-10
+        span.remove_range(5, 6);
+        assert_eq!(span.ranges, vec![(1, 4), (7, 10)]);
+        // 2-4,7-10
+        span.remove(1);
+        // 2-4,7-10,12
+        span.insert(12);
+        assert_eq!(span.ranges, vec![(2, 4), (7, 10), (12, 12)]);
+
+        assert_eq!(span.count(), 8);
+        assert_eq!(span.len(), 3);
+        assert!(span.contains(3) && span.contains(12));
+        assert!(!span.contains(1) && !span.contains(5) && !span.contains(11));
+        assert!(span.contains_range(7, 10));
+        assert!(!span.contains_range(4, 7));
+        assert_eq!(span.gaps().collect::<Vec<_>>(), vec![(5, 6), (11, 11)]);
+        assert_eq!(
+            span.complement(0, 11).ranges,
+            vec![(0, 1), (5, 6), (11, 11)]
+        );
+
+        let other = IntSpan::with_range(4, 8);
+        assert_eq!(span.intersection(&other).ranges, vec![(4, 4), (7, 8)]);
+        assert_eq!(span.union(&other).ranges, vec![(2, 10), (12, 12)]);
+        assert!(span.intersects(&other));
+        assert!(!span.intersects(&IntSpan::with_range(5, 6)));
+        assert!(span.is_superset(&IntSpan::with_range(8, 9)));
+        assert!(!span.is_superset(&other));
+        assert!(span.is_superset(&IntSpan::new()));
+    }
+
+    #[test]
+    fn extremes() {
+        let mut span = IntSpan::with_range(i64::MAX - 5, i64::MAX);
+        span.add_range(i64::MAX - 10, i64::MAX - 8);
+        assert_eq!(
+            span.ranges,
+            vec![(i64::MAX - 10, i64::MAX - 8), (i64::MAX - 5, i64::MAX)]
+        );
+        span.add_range(i64::MAX - 2, i64::MAX);
+        span.insert(i64::MAX);
+        assert_eq!(span.ranges.len(), 2, "nothing merges with the wrong range");
+        span.add_range(i64::MAX - 7, i64::MAX - 6);
+        assert_eq!(span.ranges, vec![(i64::MAX - 10, i64::MAX)]);
+
+        let mut span = IntSpan::with_range(i64::MIN, i64::MIN + 1);
+        span.add_range(i64::MIN + 2, i64::MIN + 3);
+        assert_eq!(span.ranges, vec![(i64::MIN, i64::MIN + 3)]);
+
+        assert_eq!(IntSpan::with_range(i64::MIN, 0).count(), 1 << 63 | 1);
+        assert_eq!(
+            IntSpan::new().complement(i64::MIN, i64::MAX).count(),
+            u64::MAX
+        );
+        assert_eq!(
+            IntSpan::with_range(0, 0)
+                .complement(i64::MIN, i64::MAX)
+                .count(),
+            u64::MAX
+        );
+    }
+
+    /// Bounds at and around both ends of `i64`, and around zero.
+    fn extreme() -> impl Strategy<Value = i64> {
+        prop_oneof![
+            Just(i64::MIN),
+            i64::MIN..i64::MIN + 4,
+            -4..4_i64,
+            i64::MAX - 4..i64::MAX,
+            Just(i64::MAX),
+        ]
+    }
+
+    fn extreme_range() -> impl Strategy<Value = (i64, i64)> {
+        (extreme(), extreme()).prop_map(|(a, b)| (a.min(b), a.max(b)))
+    }
+
+    /// How many integers `span` covers, counted wide enough not to saturate.
+    fn exact_count(span: &IntSpan) -> i128 {
+        span.ranges()
+            .iter()
+            .map(|&(min, max)| i128::from(max) - i128::from(min) + 1)
+            .sum()
+    }
+
+    proptest! {
+        #[test]
+        fn extremes_count_and_complement(
+            ranges in prop::collection::vec(extreme_range(), 0..6),
+            (min, max) in extreme_range(),
+        ) {
+            let mut span = IntSpan::new();
+            for &(a, b) in &ranges {
+                span.add_range(a, b);
+            }
+            let exact = exact_count(&span);
+            prop_assert_eq!(span.count(), u64::try_from(exact).unwrap_or(u64::MAX));
+
+            // what's in the window either is in the span or in its complement, never both
+            let window = IntSpan::with_range(min, max);
+            let complement = span.complement(min, max);
+            prop_assert!(!complement.intersects(&span));
+            prop_assert_eq!(
+                exact_count(&complement) + exact_count(&span.intersection(&window)),
+                i128::from(max) - i128::from(min) + 1
+            );
+        }
+
+        #[test]
+        fn ranges_stay_sorted_and_disjoint(ops in ops()) {
+            let (span, bits) = build(&ops);
+
+            for &(min, max) in span.ranges() {
+                prop_assert!(min <= max);
+            }
+            // adjacent ranges would have been merged, so there is always a gap
+            for w in span.ranges().windows(2) {
+                prop_assert!(w[0].1 + 1 < w[1].0, "{:?}", span.ranges());
+            }
+            matches_bits(&span, &bits)?;
+        }
+
+        #[test]
+        fn set_operations_match_bitset(a in ops(), b in ops()) {
+            let (a, a_bits) = build(&a);
+            let (b, b_bits) = build(&b);
+            let both = |f: fn(bool, bool) -> bool| -> Vec<bool> {
+                a_bits.iter().zip(&b_bits).map(|(&x, &y)| f(x, y)).collect()
+            };
+
+            matches_bits(&a.union(&b), &both(|x, y| x || y))?;
+            matches_bits(&a.intersection(&b), &both(|x, y| x && y))?;
+            matches_bits(&a.complement(-LIMIT, LIMIT - 1), &both(|x, _| !x))?;
+            prop_assert_eq!(a.intersects(&b), both(|x, y| x && y).contains(&true));
+            prop_assert_eq!(a.is_superset(&b), both(|x, y| y && !x).iter().all(|&v| !v));
+        }
+    }
+}
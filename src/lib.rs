@@ -0,0 +1,40 @@
+//! Advent of Code 2022 solutions, one module per day.
+#![warn(clippy::pedantic)]
+#![allow(clippy::uninlined_format_args)]
+#![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub mod answers;
+pub mod cycle;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day17;
+pub mod day2;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod debugger;
+pub mod error;
+pub mod export;
+pub mod fetch;
+pub mod filesystem;
+pub mod generate;
+pub mod grid;
+pub mod input;
+pub mod intspan;
+#[cfg(test)]
+mod mock_server;
+pub mod reference;
+pub mod registry;
+pub mod scaffold;
+pub mod solution;
+pub mod submit;
+pub mod visualize;
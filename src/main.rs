@@ -0,0 +1,941 @@
+#![warn(clippy::pedantic)]
+#![allow(clippy::uninlined_format_args)]
+use aoc2022::{
+    answers::{input_hash, verdict, Answers, Expected, Verdict, DEFAULT_ANSWERS_FILE},
+    day10::Day10,
+    day7::{self, plan_deletion, Day7},
+    debugger::Debugger,
+    error::Result,
+    export::{self, exporter},
+    fetch::{HttpClient, InputCache},
+    filesystem::{Filesystem, Kind, Query},
+    generate::{self, generator},
+    input::{input_dir, InputSource},
+    reference::{self, comparer},
+    registry::{runner, DAYS},
+    scaffold,
+    solution::Solution,
+    submit::{Guesses, Submission, DEFAULT_GUESSES_FILE},
+    visualize::{self, visualizer, Animator},
+};
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, SystemTime},
+};
+
+/// Parses a day selection: a single day (`15`), `all`, or an inclusive range (`10..14`, `10..=14`).
+fn parse_days(spec: &str) -> Option<Vec<u32>> {
+    if spec == "all" {
+        return Some(DAYS.iter().map(|(day, _)| *day).collect());
+    }
+
+    if let Some((start, end)) = spec.split_once("..") {
+        let start: u32 = start.parse().ok()?;
+        let end: u32 = end.strip_prefix('=').unwrap_or(end).parse().ok()?;
+        if start > end {
+            return None;
+        }
+        Some(
+            DAYS.iter()
+                .map(|(day, _)| *day)
+                .filter(|day| (start..=end).contains(day))
+                .collect(),
+        )
+    } else {
+        let day: u32 = spec.parse().ok()?;
+        runner(day).map(|_| vec![day])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct RunArgs {
+    days: Vec<u32>,
+    input: Option<InputSource>,
+    format: Format,
+    visualize: Option<visualize::Options>,
+}
+
+/// Parses `run` arguments: a day selection, optionally followed by an input path or `-` for stdin
+/// when exactly one day is selected, plus `--format text|json` anywhere.
+///
+/// `--visualize` animates the days before solving them, with `--delay <ms>` between frames and
+/// `--every <n>` to draw only every nth frame. The animation goes to stdout too, so it can't be
+/// combined with `--format json`.
+fn parse_run_args(args: &[String]) -> Option<RunArgs> {
+    let mut format = Format::Text;
+    let mut visualize = false;
+    let mut options = visualize::Options::default();
+    let mut positional = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match iter.next()?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    _ => return None,
+                };
+            }
+            "--visualize" => visualize = true,
+            "--delay" => options.delay = Duration::from_millis(iter.next()?.parse().ok()?),
+            "--every" => options.every = iter.next()?.parse().ok().filter(|&n| n > 0)?,
+            _ => positional.push(arg),
+        }
+    }
+    if !visualize && options != visualize::Options::default() {
+        return None;
+    }
+    if visualize && format == Format::Json {
+        return None;
+    }
+
+    let (spec, rest) = positional.split_first()?;
+    let days = parse_days(spec)?;
+    if visualize && !days.iter().all(|&day| visualizer(day).is_some()) {
+        return None;
+    }
+
+    let input = match rest {
+        [] => None,
+        [input] if days.len() == 1 => Some(InputSource::from_arg(input)),
+        _ => return None,
+    };
+
+    Some(RunArgs {
+        days,
+        input,
+        format,
+        visualize: visualize.then_some(options),
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct VerifyArgs {
+    days: Vec<u32>,
+    answers: PathBuf,
+    record: bool,
+}
+
+/// Parses `verify` arguments: an optional day selection (default `all`), `--record` to store
+/// answers for inputs that have none yet, and `--answers <file>`.
+fn parse_verify_args(args: &[String]) -> Option<VerifyArgs> {
+    let mut days = None;
+    let mut answers = PathBuf::from(DEFAULT_ANSWERS_FILE);
+    let mut record = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--record" => record = true,
+            "--answers" => answers = PathBuf::from(iter.next()?),
+            spec if days.is_none() => days = Some(parse_days(spec)?),
+            _ => return None,
+        }
+    }
+
+    Some(VerifyArgs {
+        days: days.unwrap_or_else(|| parse_days("all").expect("all is always valid")),
+        answers,
+        record,
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ExportArgs {
+    day: u32,
+    output: PathBuf,
+    input: Option<InputSource>,
+    options: export::Options,
+}
+
+/// Parses `export` arguments: a day that can be exported, the `.png` or `.gif` file to write,
+/// optionally an input path or `-` for stdin, plus `--scale <n>`, `--every <n>` and
+/// `--delay <hundredths>`.
+fn parse_export_args(args: &[String]) -> Option<ExportArgs> {
+    let mut options = export::Options::default();
+    let mut positional = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--scale" => options.scale = iter.next()?.parse().ok().filter(|&n| n > 0)?,
+            "--every" => options.every = iter.next()?.parse().ok().filter(|&n| n > 0)?,
+            "--delay" => options.delay = iter.next()?.parse().ok()?,
+            _ => positional.push(arg),
+        }
+    }
+
+    let (day, output, input) = match positional.as_slice() {
+        [day, output] => (day, output, None),
+        [day, output, input] => (day, output, Some(InputSource::from_arg(input))),
+        _ => return None,
+    };
+    let day = day.parse().ok().filter(|&day| exporter(day).is_some())?;
+    let output = PathBuf::from(output);
+    if !output
+        .extension()
+        .is_some_and(|ext| ext == "png" || ext == "gif")
+    {
+        return None;
+    }
+
+    Some(ExportArgs {
+        day,
+        output,
+        input,
+        options,
+    })
+}
+
+/// Draws a day as a PNG snapshot or an animated GIF.
+fn export_image(export_args: &ExportArgs) -> Result<()> {
+    let export = exporter(export_args.day).expect("only days with an exporter are accepted");
+    let source = export_args
+        .input
+        .clone()
+        .unwrap_or_else(|| InputSource::for_day(export_args.day));
+    export(
+        &mut source.open()?,
+        &export_args.options,
+        &export_args.output,
+    )?;
+    println!("wrote {}", export_args.output.display());
+    Ok(())
+}
+
+/// Parses `gen` arguments: a day that can be generated, plus `--seed <n>` and `--size <n>`.
+fn parse_gen_args(args: &[String]) -> Option<(u32, generate::Options)> {
+    let mut options = generate::Options::default();
+    let mut day = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--seed" => options.seed = iter.next()?.parse().ok()?,
+            "--size" => options.size = Some(iter.next()?.parse().ok()?),
+            spec if day.is_none() => {
+                day = Some(spec.parse().ok().filter(|&day| generator(day).is_some())?);
+            }
+            _ => return None,
+        }
+    }
+
+    Some((day?, options))
+}
+
+/// Writes a random input for the day to stdout.
+fn gen(day: u32, options: &generate::Options) {
+    let generate = generator(day).expect("only days with a generator are accepted");
+    print!("{}", generate(options));
+}
+
+/// Parses `diff` arguments: a day with a reference, plus `--seeds <n>` and `--size <n>`.
+fn parse_diff_args(args: &[String]) -> Option<(u32, reference::Options)> {
+    let mut options = reference::Options::default();
+    let mut day = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--seeds" => options.seeds = iter.next()?.parse().ok().filter(|&n| n > 0)?,
+            "--size" => options.size = Some(iter.next()?.parse().ok()?),
+            spec if day.is_none() => {
+                day = Some(spec.parse().ok().filter(|&day| comparer(day).is_some())?);
+            }
+            _ => return None,
+        }
+    }
+
+    Some((day?, options))
+}
+
+/// Checks the day against its naive reference on generated inputs, returning whether they agreed
+/// on every one. The first input they disagree on is printed so it can be saved and replayed,
+/// after the params it needs if they aren't the defaults `run` uses.
+fn diff(day: u32, options: &reference::Options) -> Result<bool> {
+    let compare = comparer(day).expect("only days with a reference are accepted");
+    match compare(options)? {
+        None => {
+            println!(
+                "day {}: agrees with the reference on {} inputs",
+                day, options.seeds
+            );
+            Ok(true)
+        }
+        Some(divergence) => {
+            eprintln!("day {}: {}", day, divergence);
+            print!("{}", divergence.input);
+            Ok(false)
+        }
+    }
+}
+
+/// Parses `debug` arguments: at most one program path. Commands are read from stdin, so the
+/// program can't be.
+fn parse_debug_args(args: &[String]) -> Option<InputSource> {
+    match args {
+        [] => Some(InputSource::for_day(Day10::DAY)),
+        [path] if path != "-" => Some(InputSource::Path(PathBuf::from(path))),
+        _ => None,
+    }
+}
+
+/// Steps through a day 10 program, taking debugger commands from stdin.
+fn debug(source: &InputSource) -> Result<()> {
+    let program = Day10::parse(source.open()?)?;
+    Debugger::new(&program).run(io::stdin().lock(), io::stdout().lock())?;
+    Ok(())
+}
+
+/// Which report `fs` prints about the filesystem that a day 7 transcript reveals.
+#[derive(Debug, PartialEq, Eq)]
+enum FsReport {
+    Tree,
+    /// Directory sizes, at most this many levels down.
+    Du(Option<usize>),
+    Find(Query),
+    Files,
+    /// What to delete to make room, choosing from directories and also files if `files`.
+    Plan {
+        params: day7::Params,
+        files: bool,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct FsArgs {
+    report: FsReport,
+    /// Directory to report on, from the root.
+    dir: String,
+    input: Option<InputSource>,
+}
+
+/// Parses `fs` arguments: `tree`, `du`, `find`, `files` or `plan`, optionally a directory, plus
+/// `--input <transcript|->`, `-d <n>` for `du`, `--name <glob>`, `--type f|d`, `--min-size <n>`
+/// and `--max-size <n>` for `find`, and `--disk <n>`, `--needed <n>` and `--dirs-only` for `plan`.
+fn parse_fs_args(args: &[String]) -> Option<FsArgs> {
+    let (report, args) = args.split_first()?;
+    let mut query = Query::default();
+    let mut params = day7::Params::default();
+    let mut files = true;
+    let mut depth = None;
+    let mut dir = None;
+    let mut input = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" => input = Some(InputSource::from_arg(iter.next()?)),
+            "-d" if report == "du" => depth = Some(iter.next()?.parse().ok()?),
+            "--name" if report == "find" => query.name = Some(iter.next()?.clone()),
+            "--type" if report == "find" => {
+                query.kind = match iter.next()?.as_str() {
+                    "f" => Some(Kind::File),
+                    "d" => Some(Kind::Directory),
+                    _ => return None,
+                }
+            }
+            "--min-size" if report == "find" => query.min_size = Some(iter.next()?.parse().ok()?),
+            "--max-size" if report == "find" => query.max_size = Some(iter.next()?.parse().ok()?),
+            "--disk" if report == "plan" => params.disk_size = iter.next()?.parse().ok()?,
+            "--needed" if report == "plan" => params.needed_space = iter.next()?.parse().ok()?,
+            "--dirs-only" if report == "plan" => files = false,
+            path if dir.is_none() && !path.starts_with('-') && report != "plan" => {
+                dir = Some(path.to_string());
+            }
+            _ => return None,
+        }
+    }
+
+    let report = match report.as_str() {
+        "tree" => FsReport::Tree,
+        "du" => FsReport::Du(depth),
+        "find" => FsReport::Find(query),
+        "files" => FsReport::Files,
+        "plan" => FsReport::Plan { params, files },
+        _ => return None,
+    };
+    Some(FsArgs {
+        report,
+        dir: dir.unwrap_or_else(|| "/".to_string()),
+        input,
+    })
+}
+
+/// Prints a report on the filesystem pieced together from a day 7 transcript.
+fn fs_report(fs_args: &FsArgs) -> Result<()> {
+    let source = fs_args
+        .input
+        .clone()
+        .unwrap_or_else(|| InputSource::for_day(Day7::DAY));
+    let fs = Day7::parse(source.open()?)?;
+    let dir = fs
+        .lookup(&fs_args.dir)
+        .filter(|&id| fs.is_directory(id))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no directory {} in the transcript", fs_args.dir),
+            )
+        })?;
+
+    match &fs_args.report {
+        FsReport::Tree => print!("{}", fs.render_tree(dir)),
+        FsReport::Du(depth) => {
+            for id in fs.du(dir, *depth) {
+                println!("{}\t{}", fs.size(id), fs.path(id));
+            }
+        }
+        FsReport::Find(query) => {
+            for id in fs.find(dir, query) {
+                println!("{}", fs.path(id));
+            }
+        }
+        FsReport::Files => {
+            for id in fs.files_by_size(dir) {
+                println!("{}\t{}", fs.size(id), fs.path(id));
+            }
+        }
+        FsReport::Plan { params, files } => {
+            let used = fs.size(Filesystem::ROOT);
+            match plan_deletion(&fs, params.to_free(used), *files) {
+                Some(plan) if plan.nodes.is_empty() => println!(
+                    "nothing to delete: {} of {} is free",
+                    params.disk_size.saturating_sub(used),
+                    params.disk_size
+                ),
+                Some(plan) => print!("{}", plan.explain(&fs)),
+                None => println!("deleting everything still wouldn't free enough"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn usage() -> ! {
+    eprintln!("usage: aoc22 run <day|all|start..end> [input|-] [--format text|json]");
+    eprintln!("           [--visualize [--delay <ms>] [--every <n>]]  (days 9, 10, 14 and 17)");
+    eprintln!("       aoc22 verify [day|all|start..end] [--record] [--answers <file>]");
+    eprintln!(
+        "       aoc22 export <day> <file.png|file.gif> [input|-] [--scale <n>] [--every <n>]"
+    );
+    eprintln!("           [--delay <hundredths>]  (days 8, 12, 14 and 17)");
+    eprintln!("       aoc22 gen <day> [--seed <n>] [--size <n>]");
+    eprintln!("       aoc22 diff <day> [--seeds <n>] [--size <n>]  (days 8, 15 and 17)");
+    eprintln!("       aoc22 fs <tree|du|find|files> [dir] [--input <transcript|->] [-d <n>]");
+    eprintln!("           [--name <glob>] [--type f|d] [--min-size <n>] [--max-size <n>]");
+    eprintln!("       aoc22 fs plan [--input <transcript|->] [--disk <n>] [--needed <n>]");
+    eprintln!("           [--dirs-only]");
+    eprintln!("       aoc22 debug [program]  (day 10)");
+    eprintln!("       aoc22 fetch [day|all|start..end]");
+    eprintln!("       aoc22 submit <day> <1|2> [answer] [--guesses <file>]");
+    eprintln!("       aoc22 new <day>");
+    process::exit(2);
+}
+
+fn run(run_args: RunArgs) -> Result<()> {
+    let show_header = run_args.days.len() > 1 && run_args.format == Format::Text;
+    for day in run_args.days {
+        if show_header {
+            println!("== Day {} ==", day);
+        }
+        let run = runner(day).expect("day came from the registry");
+        let source = run_args
+            .input
+            .clone()
+            .unwrap_or_else(|| InputSource::for_day(day));
+        let report = if let Some(options) = run_args.visualize {
+            // read it all up front, since stdin can't be opened a second time to solve
+            let mut data = Vec::new();
+            source.open()?.read_to_end(&mut data)?;
+            let visualize = visualizer(day).expect("only days with a visualizer are accepted");
+            visualize(
+                &mut data.as_slice(),
+                &mut Animator::new(&mut io::stdout(), options),
+            )?;
+            run(&mut data.as_slice())?
+        } else {
+            run(&mut source.open()?)?
+        };
+
+        match run_args.format {
+            Format::Text => {
+                println!("{}", report.part1);
+                println!("{}", report.part2);
+            }
+            // one object per line, so several days form a JSON Lines stream
+            Format::Json => println!(
+                "{}",
+                serde_json::to_string(&report).expect("reports always serialize")
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs each day against its real input and compares with the recorded answers, returning whether
+/// everything that had an answer matched.
+fn verify(verify_args: &VerifyArgs) -> Result<bool> {
+    let mut answers = Answers::load(&verify_args.answers)?;
+    let mut all_passed = true;
+    let mut recorded = false;
+
+    for &day in &verify_args.days {
+        let InputSource::Path(path) = InputSource::for_day(day) else {
+            unreachable!("real inputs always come from a file");
+        };
+        if !path.exists() {
+            println!("day {:2}: no input at {}", day, path.display());
+            continue;
+        }
+
+        let data = fs::read(&path)?;
+        let hash = input_hash(&data);
+        let run = runner(day).expect("day came from the registry");
+        let report = run(&mut data.as_slice())?;
+
+        match verdict(answers.get(day, &hash), &report) {
+            Verdict::Pass => println!("day {:2}: pass", day),
+            Verdict::Fail(mismatches) => {
+                all_passed = false;
+                println!("day {:2}: FAIL", day);
+                for mismatch in mismatches {
+                    print!("{}", mismatch);
+                }
+            }
+            Verdict::Missing if verify_args.record => {
+                println!("day {:2}: recorded (input {})", day, hash);
+                answers.insert(day, &hash, Expected::from(&report));
+                recorded = true;
+            }
+            Verdict::Missing => println!("day {:2}: missing (input {})", day, hash),
+        }
+    }
+
+    if recorded {
+        answers.save(&verify_args.answers)?;
+    }
+
+    Ok(all_passed)
+}
+
+/// Downloads the real inputs for the selected days (default `all`) that aren't cached yet.
+fn fetch(args: &[String]) -> Result<()> {
+    let days = match args {
+        [] => parse_days("all"),
+        [spec] => parse_days(spec),
+        _ => None,
+    }
+    .unwrap_or_else(|| usage());
+
+    let cache = InputCache::new(input_dir(), HttpClient::from_env()?);
+    for day in days {
+        if cache.is_cached(day) {
+            println!("day {:2}: cached", day);
+        } else {
+            let path = cache.get(day)?;
+            println!("day {:2}: fetched to {}", day, path.display());
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SubmitArgs {
+    day: u32,
+    part: u8,
+    answer: Option<String>,
+    guesses: PathBuf,
+}
+
+/// Parses `submit` arguments: a day, a part, optionally the answer to send instead of the computed
+/// one, and `--guesses <file>`.
+fn parse_submit_args(args: &[String]) -> Option<SubmitArgs> {
+    let mut guesses = PathBuf::from(DEFAULT_GUESSES_FILE);
+    let mut positional = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--guesses" {
+            guesses = PathBuf::from(iter.next()?);
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let (day, part, answer) = match positional.as_slice() {
+        [day, part] => (day, part, None),
+        [day, part, answer] => (day, part, Some((*answer).clone())),
+        _ => return None,
+    };
+    let day = day.parse().ok().filter(|&day| runner(day).is_some())?;
+    let part = part.parse().ok().filter(|part| matches!(part, 1 | 2))?;
+
+    Some(SubmitArgs {
+        day,
+        part,
+        answer,
+        guesses,
+    })
+}
+
+/// Solves the day's real input for the answer to `part`.
+fn computed_answer(day: u32, part: u8) -> Result<String> {
+    let run = runner(day).expect("day came from the registry");
+    let report = run(&mut InputSource::for_day(day).open()?)?;
+    let answer = if part == 1 {
+        report.part1
+    } else {
+        report.part2
+    };
+
+    if answer.contains('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the answer spans several lines; read it and pass it explicitly\n{}",
+                answer
+            ),
+        )
+        .into());
+    }
+    Ok(answer)
+}
+
+/// Sends an answer for one part, solving the day's real input for it unless one was given.
+fn submit(submit_args: SubmitArgs) -> Result<()> {
+    let answer = if let Some(answer) = submit_args.answer {
+        answer
+    } else {
+        computed_answer(submit_args.day, submit_args.part)?
+    };
+
+    let mut guesses = Guesses::load(&submit_args.guesses)?;
+    let client = HttpClient::from_env()?;
+    let submission = guesses.submit(
+        &client,
+        submit_args.day,
+        submit_args.part,
+        &answer,
+        SystemTime::now(),
+    )?;
+    guesses.save(&submit_args.guesses)?;
+
+    match submission {
+        Submission::Sent(outcome) => println!("{}: {}", answer, outcome),
+        Submission::Known(outcome) => {
+            println!("{}: {} (from earlier guesses, not sent)", answer, outcome);
+        }
+        Submission::TooSoon(wait) => {
+            println!("{}: not sent, {}s left to wait", answer, wait.as_secs());
+        }
+    }
+    Ok(())
+}
+
+/// Generates `src/dayN.rs` from the template and registers it, from the root of the crate.
+fn new_day(args: &[String]) -> Result<()> {
+    let day = match args {
+        [day] => day.parse::<u32>().ok().filter(|day| (1..=25).contains(day)),
+        _ => None,
+    }
+    .unwrap_or_else(|| usage());
+
+    scaffold::create(Path::new("."), day)?;
+    println!("created src/day{0}.rs and inputs-sample/day{0}", day);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.split_first() {
+        Some((command, rest)) if command == "run" => {
+            run(parse_run_args(rest).unwrap_or_else(|| usage()))
+        }
+        Some((command, rest)) if command == "verify" => {
+            verify(&parse_verify_args(rest).unwrap_or_else(|| usage())).map(|all_passed| {
+                if !all_passed {
+                    process::exit(1);
+                }
+            })
+        }
+        Some((command, rest)) if command == "export" => {
+            export_image(&parse_export_args(rest).unwrap_or_else(|| usage()))
+        }
+        Some((command, rest)) if command == "gen" => {
+            let (day, options) = parse_gen_args(rest).unwrap_or_else(|| usage());
+            gen(day, &options);
+            Ok(())
+        }
+        Some((command, rest)) if command == "diff" => {
+            let (day, options) = parse_diff_args(rest).unwrap_or_else(|| usage());
+            diff(day, &options).map(|agreed| {
+                if !agreed {
+                    process::exit(1);
+                }
+            })
+        }
+        Some((command, rest)) if command == "fs" => {
+            fs_report(&parse_fs_args(rest).unwrap_or_else(|| usage()))
+        }
+        Some((command, rest)) if command == "debug" => {
+            debug(&parse_debug_args(rest).unwrap_or_else(|| usage()))
+        }
+        Some((command, rest)) if command == "fetch" => fetch(rest),
+        Some((command, rest)) if command == "submit" => {
+            submit(parse_submit_args(rest).unwrap_or_else(|| usage()))
+        }
+        Some((command, rest)) if command == "new" => new_day(rest),
+        _ => usage(),
+    };
+
+    if let Err(e) = result {
+        eprintln!("aoc22: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        parse_days, parse_debug_args, parse_diff_args, parse_export_args, parse_fs_args,
+        parse_gen_args, parse_run_args, parse_submit_args, parse_verify_args, ExportArgs, Format,
+        FsArgs, FsReport, RunArgs,
+    };
+    use aoc2022::{
+        day7, export,
+        filesystem::{Kind, Query},
+        generate,
+        input::InputSource,
+        reference, visualize,
+    };
+    use std::{path::PathBuf, time::Duration};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn day_selection() {
+        assert_eq!(parse_days("15"), Some(vec![15]));
+        assert_eq!(parse_days("16"), None);
+        assert_eq!(parse_days("10..14"), Some(vec![10, 11, 12, 13, 14]));
+        assert_eq!(parse_days("14..=17"), Some(vec![14, 15, 17]));
+        assert_eq!(parse_days("14..10"), None);
+        assert_eq!(parse_days("all").map(|days| days.len()), Some(17));
+    }
+
+    #[test]
+    fn run_arguments() {
+        assert_eq!(
+            parse_run_args(&args(&["6", "-"])),
+            Some(RunArgs {
+                days: vec![6],
+                input: Some(InputSource::Stdin),
+                format: Format::Text,
+                visualize: None,
+            })
+        );
+        assert_eq!(
+            parse_run_args(&args(&["--format", "json", "1..3"])).map(|run| run.format),
+            Some(Format::Json)
+        );
+        assert_eq!(parse_run_args(&args(&["1", "--format", "xml"])), None);
+        assert_eq!(
+            parse_run_args(&args(&["all"])).map(|run| run.input),
+            Some(None)
+        );
+        assert_eq!(parse_run_args(&args(&["1..3", "-"])), None);
+        assert_eq!(
+            parse_run_args(&args(&[
+                "14",
+                "--visualize",
+                "--every",
+                "5",
+                "--delay",
+                "0"
+            ]))
+            .and_then(|run| run.visualize),
+            Some(visualize::Options {
+                delay: Duration::ZERO,
+                every: 5,
+            })
+        );
+        assert_eq!(parse_run_args(&args(&["14", "--every", "5"])), None);
+        assert_eq!(parse_run_args(&args(&["1", "--visualize"])), None);
+        assert_eq!(
+            parse_run_args(&args(&["14", "--visualize", "--format", "json"])),
+            None,
+            "frames would end up among the JSON lines"
+        );
+        assert_eq!(parse_run_args(&args(&[])), None);
+    }
+
+    #[test]
+    fn verify_arguments() {
+        let verify = parse_verify_args(&args(&[])).unwrap();
+        assert_eq!(verify.days.len(), 17);
+        assert!(!verify.record);
+
+        let verify = parse_verify_args(&args(&["--record", "15", "--answers", "x.toml"])).unwrap();
+        assert_eq!(verify.days, vec![15]);
+        assert!(verify.record);
+        assert_eq!(verify.answers.to_str(), Some("x.toml"));
+
+        assert!(parse_verify_args(&args(&["1", "2"])).is_none());
+    }
+
+    #[test]
+    fn submit_arguments() {
+        let submit = parse_submit_args(&args(&["10", "2", "EHZFZHCZ"])).unwrap();
+        assert_eq!((submit.day, submit.part), (10, 2));
+        assert_eq!(submit.answer.as_deref(), Some("EHZFZHCZ"));
+        assert_eq!(submit.guesses.to_str(), Some("guesses.toml"));
+
+        assert!(parse_submit_args(&args(&["1", "3"])).is_none());
+        assert!(parse_submit_args(&args(&["16", "1"])).is_none());
+        assert!(parse_submit_args(&args(&["1"])).is_none());
+    }
+
+    #[test]
+    fn export_arguments() {
+        assert_eq!(
+            parse_export_args(&args(&[
+                "14", "sand.gif", "-", "--every", "10", "--scale", "2"
+            ])),
+            Some(ExportArgs {
+                day: 14,
+                output: PathBuf::from("sand.gif"),
+                input: Some(InputSource::Stdin),
+                options: export::Options {
+                    scale: 2,
+                    every: 10,
+                    ..export::Options::default()
+                },
+            })
+        );
+        assert!(parse_export_args(&args(&["8", "trees.png"])).is_some());
+        assert!(parse_export_args(&args(&["8", "trees.jpg"])).is_none());
+        assert!(parse_export_args(&args(&["1", "calories.png"])).is_none());
+        assert!(parse_export_args(&args(&["8", "trees.png", "--scale", "0"])).is_none());
+    }
+
+    #[test]
+    fn gen_arguments() {
+        assert_eq!(
+            parse_gen_args(&args(&["11", "--seed", "7", "--size", "20"])),
+            Some((
+                11,
+                generate::Options {
+                    seed: 7,
+                    size: Some(20),
+                }
+            ))
+        );
+        assert_eq!(
+            parse_gen_args(&args(&["--seed", "3", "7"])),
+            Some((
+                7,
+                generate::Options {
+                    seed: 3,
+                    size: None,
+                }
+            ))
+        );
+        assert!(parse_gen_args(&args(&["16"])).is_none());
+        assert!(parse_gen_args(&args(&["7", "8"])).is_none());
+        assert!(parse_gen_args(&args(&["7", "--size", "big"])).is_none());
+        assert!(parse_gen_args(&args(&[])).is_none());
+    }
+
+    #[test]
+    fn diff_arguments() {
+        assert_eq!(
+            parse_diff_args(&args(&["15", "--seeds", "50", "--size", "8"])),
+            Some((
+                15,
+                reference::Options {
+                    seeds: 50,
+                    size: Some(8),
+                }
+            ))
+        );
+        assert_eq!(
+            parse_diff_args(&args(&["8"])),
+            Some((8, reference::Options::default()))
+        );
+        assert!(parse_diff_args(&args(&["1"])).is_none());
+        assert!(parse_diff_args(&args(&["8", "--seeds", "0"])).is_none());
+    }
+
+    #[test]
+    fn debug_arguments() {
+        assert!(matches!(
+            parse_debug_args(&args(&[])),
+            Some(InputSource::Path(path)) if path.ends_with("day10")
+        ));
+        assert_eq!(
+            parse_debug_args(&args(&["prog.txt"])),
+            Some(InputSource::Path(PathBuf::from("prog.txt")))
+        );
+        assert!(parse_debug_args(&args(&["-"])).is_none());
+        assert!(parse_debug_args(&args(&["a", "b"])).is_none());
+    }
+
+    #[test]
+    fn fs_arguments() {
+        assert_eq!(
+            parse_fs_args(&args(&["du", "/a", "-d", "2", "--input", "-"])),
+            Some(FsArgs {
+                report: FsReport::Du(Some(2)),
+                dir: "/a".to_string(),
+                input: Some(InputSource::Stdin),
+            })
+        );
+        assert_eq!(
+            parse_fs_args(&args(&["find", "--type", "d", "--max-size", "100000"])),
+            Some(FsArgs {
+                report: FsReport::Find(Query {
+                    kind: Some(Kind::Directory),
+                    max_size: Some(100_000),
+                    ..Query::default()
+                }),
+                dir: "/".to_string(),
+                input: None,
+            })
+        );
+        assert_eq!(
+            parse_fs_args(&args(&["plan", "--needed", "40000000", "--dirs-only"])),
+            Some(FsArgs {
+                report: FsReport::Plan {
+                    params: day7::Params {
+                        needed_space: 40_000_000,
+                        ..day7::Params::default()
+                    },
+                    files: false,
+                },
+                dir: "/".to_string(),
+                input: None,
+            })
+        );
+        assert!(parse_fs_args(&args(&["plan", "/a"])).is_none());
+        assert!(parse_fs_args(&args(&["tree", "-d", "2"])).is_none());
+        assert!(parse_fs_args(&args(&["find", "--type", "x"])).is_none());
+        assert!(parse_fs_args(&args(&["files", "/a", "/b"])).is_none());
+        assert!(parse_fs_args(&args(&["ls"])).is_none());
+        assert!(parse_fs_args(&args(&[])).is_none());
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    day15, day17, day8,
+    error::Result,
+    generate::{Generate, Random},
+};
+use rand::SeedableRng;
+use std::fmt::{self, Debug, Display};
+
+/// A day with slow but obviously correct answers to check its solution against.
+pub trait Reference: Generate {
+    /// Parameters small enough for the references to finish; the defaults unless overridden.
+    #[must_use]
+    fn reference_params() -> Self::Params {
+        Self::Params::default()
+    }
+
+    /// Part 1 worked out the naive way, or `None` if there's no naive way that finishes.
+    fn reference1(_input: &Self::Input, _params: &Self::Params) -> Option<Self::Answer1> {
+        None
+    }
+
+    /// Part 2 worked out the naive way, or `None` if there's no naive way that finishes.
+    fn reference2(_input: &Self::Input, _params: &Self::Params) -> Option<Self::Answer2> {
+        None
+    }
+}
+
+/// How many generated inputs to compare on, and how large.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Inputs are generated from seeds `0..seeds`.
+    pub seeds: u64,
+    /// Passed on to the generator; `None` for the size of a real input.
+    pub size: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            seeds: 20,
+            size: None,
+        }
+    }
+}
+
+/// A generated input that a solution and its reference disagree on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub seed: u64,
+    /// The params the input was generated and solved with, as `{:?}`; `run` uses the defaults
+    /// instead, so replaying the input needs these.
+    pub params: String,
+    pub part: u8,
+    pub expected: String,
+    pub actual: String,
+    pub input: String,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seed {}", self.seed)?;
+        // days without params have nothing to replay with
+        if self.params != "()" {
+            write!(f, " with params {}", self.params)?;
+        }
+        write!(
+            f,
+            ": part {} answered {} but the reference answered {}",
+            self.part, self.actual, self.expected
+        )
+    }
+}
+
+/// Compares a day's solution with its reference on generated inputs.
+pub type Comparer = fn(&Options) -> Result<Option<Divergence>>;
+
+/// Runs both parts and their references on an input generated from each seed in turn, stopping at
+/// the first one they disagree on.
+pub fn compare<S: Reference>(options: &Options) -> Result<Option<Divergence>>
+where
+    S::Params: Debug,
+{
+    let params = S::reference_params();
+    for seed in 0..options.seeds {
+        let size = options.size.unwrap_or(S::SIZE);
+        let text = S::generate(&mut Random::seed_from_u64(seed), size, &params);
+        let input = S::parse(text.as_bytes())?;
+
+        let divergence = |part, expected: String, actual: String| Divergence {
+            seed,
+            params: format!("{:?}", params),
+            part,
+            expected,
+            actual,
+            input: text.clone(),
+        };
+        if let Some(expected) = S::reference1(&input, &params).map(|a| a.to_string()) {
+            let actual = S::part1(&input, &params).to_string();
+            if actual != expected {
+                return Ok(Some(divergence(1, expected, actual)));
+            }
+        }
+        if let Some(expected) = S::reference2(&input, &params).map(|a| a.to_string()) {
+            let actual = S::part2(&input, &params).to_string();
+            if actual != expected {
+                return Ok(Some(divergence(2, expected, actual)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Every day with a reference, in order.
+pub const REFERENCES: &[(u32, Comparer)] = &[
+    (8, compare::<day8::Day8>),
+    (15, compare::<day15::Day15>),
+    (17, compare::<day17::Day17>),
+];
+
+#[must_use]
+pub fn comparer(day: u32) -> Option<Comparer> {
+    REFERENCES
+        .iter()
+        .find(|(registered, _)| *registered == day)
+        .map(|(_, compare)| *compare)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compare, comparer, Divergence, Options, Reference, REFERENCES};
+    use crate::{
+        error::Result,
+        generate::{Generate, Random},
+        solution::Solution,
+    };
+    use std::io::BufRead;
+
+    #[test]
+    fn agree() {
+        let options = Options {
+            seeds: 5,
+            size: Some(12),
+        };
+        for (day, compare) in REFERENCES {
+            if let Some(divergence) = compare(&options).unwrap() {
+                panic!("day {}: {}\n{}", day, divergence, divergence.input);
+            }
+        }
+        assert!(comparer(1).is_none());
+    }
+
+    /// Counts lines, but gets part 2 wrong once there are more than three.
+    struct Miscounts;
+
+    impl Solution for Miscounts {
+        const DAY: u32 = 0;
+
+        type Input = usize;
+        type Params = ();
+        type Answer1 = usize;
+        type Answer2 = usize;
+
+        fn parse<R: BufRead>(reader: R) -> Result<usize> {
+            Ok(reader.lines().count())
+        }
+
+        fn part1(lines: &usize, _params: &()) -> usize {
+            *lines
+        }
+
+        fn part2(lines: &usize, _params: &()) -> usize {
+            (*lines).min(3)
+        }
+    }
+
+    impl Generate for Miscounts {
+        const SIZE: usize = 0;
+
+        fn generate(_rng: &mut Random, size: usize, _params: &()) -> String {
+            "\n".repeat(size)
+        }
+    }
+
+    impl Reference for Miscounts {
+        fn reference1(lines: &usize, _params: &()) -> Option<usize> {
+            Some(*lines)
+        }
+
+        fn reference2(lines: &usize, _params: &()) -> Option<usize> {
+            Some(*lines)
+        }
+    }
+
+    #[test]
+    fn diverges() {
+        let small = Options {
+            seeds: 3,
+            size: Some(3),
+        };
+        assert_eq!(compare::<Miscounts>(&small).unwrap(), None);
+
+        let divergence = compare::<Miscounts>(&Options {
+            seeds: 3,
+            size: Some(5),
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            (divergence.seed, divergence.part),
+            (0, 2),
+            "stops at the first"
+        );
+        assert_eq!(
+            divergence.to_string(),
+            "seed 0: part 2 answered 3 but the reference answered 5"
+        );
+
+        let divergence = Divergence {
+            params: "Params { part1_y: 10, part2_extent: 20 }".to_string(),
+            ..divergence
+        };
+        assert_eq!(
+            divergence.to_string(),
+            "seed 0 with params Params { part1_y: 10, part2_extent: 20 }: \
+             part 2 answered 3 but the reference answered 5"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use crate::error::Result;
+use crate::solution::{run, Report};
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day17, day2, day25, day3, day4, day5, day6,
+    day7, day8, day9,
+};
+use std::io::BufRead;
+
+/// Solves the puzzle input read from the reader.
+pub type Runner = fn(&mut dyn BufRead) -> Result<Report>;
+
+/// Every implemented day, in order.
+pub const DAYS: &[(u32, Runner)] = &[
+    (1, run::<day1::Day1>),
+    (2, run::<day2::Day2>),
+    (3, run::<day3::Day3>),
+    (4, run::<day4::Day4>),
+    (5, run::<day5::Day5>),
+    (6, run::<day6::Day6>),
+    (7, run::<day7::Day7>),
+    (8, run::<day8::Day8>),
+    (9, run::<day9::Day9>),
+    (10, run::<day10::Day10>),
+    (11, run::<day11::Day11>),
+    (12, run::<day12::Day12>),
+    (13, run::<day13::Day13>),
+    (14, run::<day14::Day14>),
+    (15, run::<day15::Day15>),
+    (17, run::<day17::Day17>),
+    (25, run::<day25::Day25>),
+];
+
+#[must_use]
+pub fn runner(day: u32) -> Option<Runner> {
+    DAYS.iter()
+        .find(|(registered, _)| *registered == day)
+        .map(|(_, run)| *run)
+}
@@ -0,0 +1,190 @@
+use crate::error::Result;
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+/// Starting point for a new day; every `__DAY__` is replaced with the day number.
+const TEMPLATE: &str = include_str!("base.rs");
+
+/// The source of a new, empty solution for `day`.
+#[must_use]
+pub fn render(day: u32) -> String {
+    TEMPLATE.replace("__DAY__", &day.to_string())
+}
+
+/// Adds `pub mod dayN;` to `lib.rs`, keeping the declarations sorted.
+#[must_use]
+pub fn add_module(lib_rs: &str, day: u32) -> Option<String> {
+    let module = format!("pub mod day{};", day);
+    let mut lines: Vec<&str> = lib_rs.lines().collect();
+    let first = lines.iter().position(|line| line.starts_with("pub mod "))?;
+    let count = lines[first..]
+        .iter()
+        .take_while(|line| line.starts_with("pub mod "))
+        .count();
+
+    let mut modules = lines.split_off(first);
+    let rest = modules.split_off(count);
+    if modules.contains(&module.as_str()) {
+        return None;
+    }
+    modules.push(&module);
+    modules.sort_unstable_by_key(|line| line.trim_end_matches(';'));
+
+    lines.extend(modules);
+    lines.extend(rest);
+    Some(lines.join("\n") + "\n")
+}
+
+/// Imports `dayN` in `registry.rs` and adds it to `DAYS` in day order.
+#[must_use]
+pub fn add_to_registry(registry_rs: &str, day: u32) -> Option<String> {
+    let module = format!("day{}", day);
+
+    // the `use crate::{...};` list of day modules
+    let start = registry_rs.find("use crate::{")?;
+    let end = start + registry_rs[start..].find("};")? + 2;
+    let mut modules: Vec<&str> = registry_rs[start + "use crate::{".len()..end - 2]
+        .split(',')
+        .map(str::trim)
+        .filter(|module| !module.is_empty())
+        .collect();
+    if modules.contains(&module.as_str()) {
+        return None;
+    }
+    modules.push(&module);
+    modules.sort_unstable();
+    let imports = wrap_use("use crate::{", &modules, "};");
+
+    // the `DAYS` table, one `(N, run::<dayN::DayN>),` per line
+    let mut lines: Vec<String> = registry_rs[end..].lines().map(String::from).collect();
+    let entry_day = |line: &str| -> Option<u32> {
+        let (day, rest) = line.trim().strip_prefix('(')?.split_once(',')?;
+        if !rest.contains("run::<") {
+            return None;
+        }
+        day.parse().ok()
+    };
+    let last_entry = lines.iter().rposition(|line| entry_day(line).is_some())?;
+    let insert_at = lines
+        .iter()
+        .position(|line| entry_day(line).is_some_and(|d| d > day))
+        .unwrap_or(last_entry + 1);
+    lines.insert(
+        insert_at,
+        format!("    ({}, run::<day{}::Day{}>),", day, day, day),
+    );
+
+    Some(format!(
+        "{}{}{}\n",
+        &registry_rs[..start],
+        imports,
+        lines.join("\n")
+    ))
+}
+
+/// Lays out a `use` list the way rustfmt would: on one line if it fits, otherwise one indented
+/// block filled up to the line width.
+fn wrap_use(open: &str, items: &[&str], close: &str) -> String {
+    const MAX_WIDTH: usize = 100;
+
+    let one_line = format!("{}{}{}", open, items.join(", "), close);
+    if one_line.len() <= MAX_WIDTH {
+        return one_line;
+    }
+
+    let mut out = format!("{}\n", open);
+    let mut line = String::new();
+    for item in items {
+        if !line.is_empty() && line.len() + 1 + item.len() + 1 > MAX_WIDTH {
+            out.push_str(line.trim_end());
+            out.push('\n');
+            line.clear();
+        }
+        if line.is_empty() {
+            line.push_str("   ");
+        }
+        line.push(' ');
+        line.push_str(item);
+        line.push(',');
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out.push_str(close);
+    out
+}
+
+fn unexpected_layout(path: &Path) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "couldn't find where to add the new day in {}",
+            path.display()
+        ),
+    )
+}
+
+/// Creates `src/dayN.rs` and an empty `inputs-sample/dayN` under `root`, and registers the day.
+pub fn create(root: &Path, day: u32) -> Result<()> {
+    let source = root.join(format!("src/day{}.rs", day));
+    if source.exists() {
+        return Err(io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", source.display()),
+        )
+        .into());
+    }
+
+    let lib = root.join("src/lib.rs");
+    let registry = root.join("src/registry.rs");
+    let new_lib =
+        add_module(&fs::read_to_string(&lib)?, day).ok_or_else(|| unexpected_layout(&lib))?;
+    let new_registry = add_to_registry(&fs::read_to_string(&registry)?, day)
+        .ok_or_else(|| unexpected_layout(&registry))?;
+
+    fs::write(&source, render(day))?;
+    fs::write(&lib, new_lib)?;
+    fs::write(&registry, new_registry)?;
+
+    let sample = root.join(format!("inputs-sample/day{}", day));
+    if !sample.exists() {
+        fs::write(sample, "")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{add_module, add_to_registry, render};
+
+    #[test]
+    fn template() {
+        let source = render(16);
+
+        assert!(source.contains("pub struct Day16;"));
+        assert!(source.contains("const DAY: u32 = 16;"));
+        assert!(source.contains("\"inputs-sample/day16\""));
+        assert!(!source.contains("__DAY__"));
+    }
+
+    #[test]
+    fn registration() {
+        let lib = add_module(include_str!("lib.rs"), 16).unwrap();
+        assert!(lib.contains("pub mod day15;\npub mod day16;\npub mod day17;\n"));
+        assert!(lib.contains("pub mod cycle;\npub mod day1;\npub mod day10;\n"));
+        assert_eq!(add_module(&lib, 16), None);
+
+        let registry = add_to_registry(include_str!("registry.rs"), 16).unwrap();
+        assert!(registry.contains("day15, day16, day17,"));
+        assert!(registry.contains(
+            "    (15, run::<day15::Day15>),\n    (16, run::<day16::Day16>),\n    (17, run::<day17::Day17>),\n"
+        ));
+        assert_eq!(add_to_registry(&registry, 16), None);
+
+        let registry = add_to_registry(include_str!("registry.rs"), 26).unwrap();
+        assert!(registry.contains("(25, run::<day25::Day25>),\n    (26, run::<day26::Day26>),\n];"));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::error::Result;
+use adventlib::aoc;
+use serde::Serialize;
+use std::{fmt::Display, io::BufRead, time::Instant};
+
+/// A single day's puzzle, split into parsing and the two parts so every day can be driven the
+/// same way.
+pub trait Solution {
+    /// Day of the advent calendar this puzzle belongs to.
+    const DAY: u32;
+
+    /// Parsed puzzle input, shared by both parts.
+    type Input;
+    /// Extra knobs a day needs beyond its input; `()` for most days.
+    type Params: Default;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse<R: BufRead>(reader: R) -> Result<Self::Input>;
+    fn part1(input: &Self::Input, params: &Self::Params) -> Self::Answer1;
+    fn part2(input: &Self::Input, params: &Self::Params) -> Self::Answer2;
+}
+
+/// Parses `reader` and runs both parts with explicit parameters.
+pub fn solve_with<S: Solution, R: BufRead>(
+    reader: R,
+    params: &S::Params,
+) -> Result<(S::Answer1, S::Answer2)> {
+    let input = S::parse(reader)?;
+
+    Ok((S::part1(&input, params), S::part2(&input, params)))
+}
+
+/// Parses `reader` and runs both parts with the day's default parameters.
+pub fn solve<S: Solution, R: BufRead>(reader: R) -> Result<(S::Answer1, S::Answer2)> {
+    solve_with::<S, R>(reader, &S::Params::default())
+}
+
+/// Opens `filename` and solves it with the day's default parameters.
+pub fn solve_file<S: Solution>(filename: &str) -> Result<(S::Answer1, S::Answer2)> {
+    solve::<S, _>(aoc::file(filename)?)
+}
+
+/// Wall-clock time spent in each phase, in seconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Timings {
+    pub parse: f64,
+    pub part1: f64,
+    pub part2: f64,
+}
+
+/// Both answers for a day, rendered as text, along with how long each phase took.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub day: u32,
+    pub part1: String,
+    pub part2: String,
+    pub timings: Timings,
+}
+
+/// Solves `reader` with the day's default parameters, timing each phase separately.
+pub fn run<S: Solution>(reader: &mut dyn BufRead) -> Result<Report> {
+    let params = S::Params::default();
+
+    let start = Instant::now();
+    let input = S::parse(reader)?;
+    let parse = start.elapsed();
+
+    let start = Instant::now();
+    let part1 = S::part1(&input, &params).to_string();
+    let part1_time = start.elapsed();
+
+    let start = Instant::now();
+    let part2 = S::part2(&input, &params).to_string();
+    let part2_time = start.elapsed();
+
+    Ok(Report {
+        day: S::DAY,
+        part1,
+        part2,
+        timings: Timings {
+            parse: parse.as_secs_f64(),
+            part1: part1_time.as_secs_f64(),
+            part2: part2_time.as_secs_f64(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::run;
+    use crate::day2::Day2;
+
+    #[test]
+    fn report_json() {
+        let report = run::<Day2>(&mut "A Y\nB X\nC Z\n".as_bytes()).unwrap();
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["day"], 2);
+        assert_eq!(json["part1"], "15");
+        assert_eq!(json["part2"], "12");
+        for phase in ["parse", "part1", "part2"] {
+            assert!(json["timings"][phase].as_f64().unwrap() >= 0.0);
+        }
+    }
+}
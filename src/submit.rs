@@ -0,0 +1,388 @@
+use crate::{
+    answers::{load_toml, save_toml},
+    error::Result,
+    fetch::HttpClient,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Where past guesses are kept unless told otherwise.
+pub const DEFAULT_GUESSES_FILE: &str = "guesses.toml";
+
+/// How long to hold off when the site asks us to wait without saying for how long.
+const DEFAULT_WAIT_SECS: u64 = 60;
+
+/// Somewhere answers can be sent; returns the page that came back.
+pub trait Submitter {
+    fn submit_answer(&self, day: u32, part: u8, answer: &str) -> Result<String>;
+}
+
+impl Submitter for HttpClient {
+    fn submit_answer(&self, day: u32, part: u8, answer: &str) -> Result<String> {
+        self.post_form(
+            &self.day_url(day, "answer"),
+            &[("level", &part.to_string()), ("answer", answer)],
+        )
+    }
+}
+
+/// What the site said about an answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// The site wants us to wait this long before answering again.
+    #[serde(skip)]
+    Wait(Duration),
+    /// The part has already been solved, or isn't unlocked yet.
+    #[serde(skip)]
+    WrongLevel,
+    #[serde(skip)]
+    Unrecognized(String),
+}
+
+impl Outcome {
+    /// Whether the answer itself was judged, and so is worth remembering.
+    fn is_judgement(&self) -> bool {
+        matches!(
+            self,
+            Self::Correct | Self::Wrong | Self::TooHigh | Self::TooLow
+        )
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Correct => write!(f, "correct"),
+            Self::Wrong => write!(f, "wrong"),
+            Self::TooHigh => write!(f, "wrong, too high"),
+            Self::TooLow => write!(f, "wrong, too low"),
+            Self::Wait(wait) => {
+                write!(f, "answered too recently, {}s left to wait", wait.as_secs())
+            }
+            Self::WrongLevel => write!(f, "already solved or not unlocked yet"),
+            Self::Unrecognized(text) => write!(f, "unrecognized response: {}", text),
+        }
+    }
+}
+
+/// The text of the page's `<article>`, with tags removed and whitespace collapsed.
+fn article_text(html: &str) -> String {
+    let start = html.find("<article").unwrap_or(0);
+    let end = html[start..]
+        .find("</article>")
+        .map_or(html.len(), |end| start + end);
+
+    let mut text = String::new();
+    let mut in_tag = false;
+    for ch in html[start..end].chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            ch if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parses durations the site writes as `1m 23s`, `45s`, `one minute` or `5 minutes`.
+fn parse_wait(text: &str) -> Option<Duration> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut seconds = 0;
+    let mut found = false;
+    for (i, word) in words.iter().enumerate() {
+        let word = word.trim_end_matches(['.', ',']);
+        if let Some(minutes) = word.strip_suffix('m').and_then(|m| m.parse::<u64>().ok()) {
+            seconds += minutes * 60;
+            found = true;
+        } else if let Some(secs) = word.strip_suffix('s').and_then(|s| s.parse::<u64>().ok()) {
+            seconds += secs;
+            found = true;
+        } else if word.starts_with("minute") && i > 0 {
+            let count = match words[i - 1] {
+                "one" | "a" => Some(1),
+                count => count.parse().ok(),
+            };
+            if let Some(count) = count {
+                seconds += count * 60;
+                found = true;
+            }
+        }
+    }
+    found.then(|| Duration::from_secs(seconds))
+}
+
+/// Works out what the site thought of an answer from the page it sent back.
+#[must_use]
+pub fn parse_response(html: &str) -> Outcome {
+    let text = article_text(html);
+
+    if text.contains("That's the right answer") {
+        Outcome::Correct
+    } else if text.contains("That's not the right answer") {
+        if text.contains("too high") {
+            Outcome::TooHigh
+        } else if text.contains("too low") {
+            Outcome::TooLow
+        } else {
+            Outcome::Wrong
+        }
+    } else if text.contains("You gave an answer too recently") {
+        let remaining = text
+            .split_once("You have ")
+            .and_then(|(_, rest)| rest.split_once(" left to wait"))
+            .and_then(|(wait, _)| parse_wait(wait));
+        Outcome::Wait(remaining.unwrap_or(Duration::from_secs(DEFAULT_WAIT_SECS)))
+    } else if text.contains("You don't seem to be solving the right level") {
+        Outcome::WrongLevel
+    } else {
+        Outcome::Unrecognized(text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Guess {
+    pub answer: String,
+    pub outcome: Outcome,
+}
+
+/// Every answer sent so far and what became of it, stored as `[days.dayN]` tables in TOML.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Guesses {
+    /// Unix time before which the site won't take another answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wait_until: Option<u64>,
+    #[serde(default)]
+    days: BTreeMap<String, BTreeMap<String, Vec<Guess>>>,
+}
+
+/// What happened when asked to submit an answer.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Submission {
+    /// The answer was sent and the site replied.
+    Sent(Outcome),
+    /// Earlier guesses already tell us how this answer fares, so it wasn't sent.
+    Known(Outcome),
+    /// The site is still rate limiting us for this long.
+    TooSoon(Duration),
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+impl Guesses {
+    /// Loads guesses from `path`; a missing file means nothing has been guessed yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        load_toml(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_toml(self, path)
+    }
+
+    #[must_use]
+    pub fn get(&self, day: u32, part: u8) -> &[Guess] {
+        self.days
+            .get(&format!("day{}", day))
+            .and_then(|parts| parts.get(&format!("part{}", part)))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn record(&mut self, day: u32, part: u8, guess: Guess) {
+        self.days
+            .entry(format!("day{}", day))
+            .or_default()
+            .entry(format!("part{}", part))
+            .or_default()
+            .push(guess);
+    }
+
+    /// What earlier guesses say about `answer` without asking the site, if anything.
+    #[must_use]
+    pub fn known_outcome(&self, day: u32, part: u8, answer: &str) -> Option<Outcome> {
+        let guesses = self.get(day, part);
+        if let Some(guess) = guesses.iter().find(|guess| guess.answer == answer) {
+            return Some(guess.outcome.clone());
+        }
+        if guesses
+            .iter()
+            .any(|guess| guess.outcome == Outcome::Correct)
+        {
+            return Some(Outcome::Wrong);
+        }
+
+        // a numeric answer past a bound we've already hit is wrong too
+        let value: i64 = answer.parse().ok()?;
+        guesses.iter().find_map(|guess| {
+            let bound: i64 = guess.answer.parse().ok()?;
+            match guess.outcome {
+                Outcome::TooHigh if value >= bound => Some(Outcome::TooHigh),
+                Outcome::TooLow if value <= bound => Some(Outcome::TooLow),
+                _ => None,
+            }
+        })
+    }
+
+    /// Sends `answer` for `day`'s `part` unless earlier guesses or the rate limit already settle
+    /// it, then remembers what the site said.
+    pub fn submit<S: Submitter>(
+        &mut self,
+        submitter: &S,
+        day: u32,
+        part: u8,
+        answer: &str,
+        now: SystemTime,
+    ) -> Result<Submission> {
+        if let Some(outcome) = self.known_outcome(day, part, answer) {
+            return Ok(Submission::Known(outcome));
+        }
+        let now = unix_time(now);
+        if let Some(wait_until) = self.wait_until.filter(|&until| until > now) {
+            return Ok(Submission::TooSoon(Duration::from_secs(wait_until - now)));
+        }
+
+        let page = submitter.submit_answer(day, part, answer)?;
+        let outcome = parse_response(&page);
+
+        self.wait_until = match &outcome {
+            Outcome::Wait(wait) => Some(now + wait.as_secs()),
+            Outcome::Wrong | Outcome::TooHigh | Outcome::TooLow => {
+                let text = article_text(&page).to_lowercase();
+                text.split_once("please wait ")
+                    .and_then(|(_, rest)| rest.split_once(" before trying again"))
+                    .and_then(|(wait, _)| parse_wait(wait))
+                    .map(|wait| now + wait.as_secs())
+            }
+            _ => None,
+        };
+        if outcome.is_judgement() {
+            self.record(
+                day,
+                part,
+                Guess {
+                    answer: answer.to_string(),
+                    outcome: outcome.clone(),
+                },
+            );
+        }
+
+        Ok(Submission::Sent(outcome))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_response, Guesses, Outcome, Submission};
+    use crate::{fetch::HttpClient, mock_server::MockServer};
+    use std::time::{Duration, SystemTime};
+
+    fn page(article: &str) -> String {
+        format!(
+            "<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>",
+            article
+        )
+    }
+
+    #[test]
+    fn responses() {
+        assert_eq!(
+            parse_response(&page(
+                "That's the right answer! You are <em>one gold star</em> closer."
+            )),
+            Outcome::Correct
+        );
+        assert_eq!(
+            parse_response(&page("That's not the right answer; your answer is too high. If you're stuck, make sure you're using the full input data; please wait one minute before trying again.")),
+            Outcome::TooHigh
+        );
+        assert_eq!(
+            parse_response(&page(
+                "That's not the right answer; your answer is too low."
+            )),
+            Outcome::TooLow
+        );
+        assert_eq!(
+            parse_response(&page("That's not the right answer. If you're stuck, ...")),
+            Outcome::Wrong
+        );
+        assert_eq!(
+            parse_response(&page("You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 23s left to wait.")),
+            Outcome::Wait(Duration::from_secs(83))
+        );
+        assert_eq!(
+            parse_response(&page(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            )),
+            Outcome::WrongLevel
+        );
+        assert_eq!(
+            parse_response(&page("Something   else")),
+            Outcome::Unrecognized("Something else".to_string())
+        );
+    }
+
+    #[test]
+    fn submissions() {
+        let server = MockServer::start(|request| {
+            let article = match request.body.as_str() {
+                "level=1&answer=100" => "That's not the right answer; your answer is too high. Please wait one minute before trying again.",
+                "level=1&answer=42" => "That's the right answer!",
+                _ => "You don't seem to be solving the right level.",
+            };
+            (200, page(article))
+        });
+        let client = HttpClient::new(server.base_url(), "cafe");
+        let start = SystemTime::now();
+        let mut guesses = Guesses::default();
+
+        assert_eq!(
+            guesses.submit(&client, 1, 1, "100", start).unwrap(),
+            Submission::Sent(Outcome::TooHigh)
+        );
+        // never resent, and anything higher is known to be too high as well
+        assert_eq!(
+            guesses.submit(&client, 1, 1, "100", start).unwrap(),
+            Submission::Known(Outcome::TooHigh)
+        );
+        assert_eq!(
+            guesses.submit(&client, 1, 1, "150", start).unwrap(),
+            Submission::Known(Outcome::TooHigh)
+        );
+        // the wrong answer asked for a minute's wait
+        assert!(matches!(
+            guesses.submit(&client, 1, 1, "42", start).unwrap(),
+            Submission::TooSoon(wait) if wait.as_secs() == 60
+        ));
+
+        let later = start + Duration::from_secs(61);
+        assert_eq!(
+            guesses.submit(&client, 1, 1, "42", later).unwrap(),
+            Submission::Sent(Outcome::Correct)
+        );
+        assert_eq!(
+            guesses.submit(&client, 1, 1, "41", later).unwrap(),
+            Submission::Known(Outcome::Wrong)
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/2022/day/1/answer");
+
+        let guesses: Guesses = toml::from_str(&toml::to_string(&guesses).unwrap()).unwrap();
+        assert_eq!(guesses.get(1, 1).len(), 2);
+        assert_eq!(guesses.get(1, 2).len(), 0);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{day10, day14, day17, day9, error::Result, solution::Solution};
+use console_bitmap::{draw_from_vec, BraillePatterns};
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+    thread,
+    time::Duration,
+};
+
+/// Clears the terminal and moves the cursor back to the top left.
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// How quickly and how densely an animation is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Pause after each frame that is drawn.
+    pub delay: Duration,
+    /// Only every `every`th frame is drawn, so long simulations finish in reasonable time.
+    pub every: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(50),
+            every: 1,
+        }
+    }
+}
+
+/// One picture of a simulation.
+pub enum Frame {
+    /// Drawn as is.
+    Text(String),
+    /// Lit pixels, one row per scanline, packed into braille characters.
+    Pixels(Vec<Vec<bool>>),
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frame::Text(text) => write!(f, "{}", text),
+            Frame::Pixels(pixels) => {
+                let lines = draw_from_vec::<BraillePatterns>(pixels);
+                for (i, line) in lines.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", line)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Draws frames to a terminal, skipping and pacing them according to its [`Options`].
+pub struct Animator<'a> {
+    out: &'a mut dyn Write,
+    options: Options,
+    /// Frames still to be skipped before the next one is drawn.
+    skip: usize,
+}
+
+impl<'a> Animator<'a> {
+    pub fn new(out: &'a mut dyn Write, options: Options) -> Self {
+        Self {
+            out,
+            options,
+            skip: 0,
+        }
+    }
+
+    /// Offers the next frame; `render` is only called for the frames that get drawn, so skipped
+    /// ones cost nothing.
+    pub fn frame(&mut self, render: impl FnOnce() -> Frame) -> io::Result<()> {
+        if self.skip == 0 {
+            self.skip = self.options.every.saturating_sub(1);
+            self.draw(&render())
+        } else {
+            self.skip -= 1;
+            Ok(())
+        }
+    }
+
+    /// Draws the final state, whether or not it falls on a frame that would be drawn.
+    pub fn finish(&mut self, frame: &Frame) -> io::Result<()> {
+        self.draw(frame)
+    }
+
+    fn draw(&mut self, frame: &Frame) -> io::Result<()> {
+        writeln!(self.out, "{}{}", CLEAR, frame)?;
+        self.out.flush()?;
+        if !self.options.delay.is_zero() {
+            thread::sleep(self.options.delay);
+        }
+        Ok(())
+    }
+}
+
+/// A day whose simulation can be watched as it runs.
+pub trait Visualize: Solution {
+    fn visualize(
+        input: &Self::Input,
+        params: &Self::Params,
+        animator: &mut Animator<'_>,
+    ) -> io::Result<()>;
+}
+
+/// Animates the puzzle input read from the reader.
+pub type Visualizer = fn(&mut dyn BufRead, &mut Animator<'_>) -> Result<()>;
+
+/// Parses `reader` and animates it with the day's default parameters.
+pub fn animate<S: Visualize>(reader: &mut dyn BufRead, animator: &mut Animator<'_>) -> Result<()> {
+    let input = S::parse(reader)?;
+    Ok(S::visualize(&input, &S::Params::default(), animator)?)
+}
+
+/// Every day that can be visualized, in order.
+pub const VISUALIZERS: &[(u32, Visualizer)] = &[
+    (9, animate::<day9::Day9>),
+    (10, animate::<day10::Day10>),
+    (14, animate::<day14::Day14>),
+    (17, animate::<day17::Day17>),
+];
+
+#[must_use]
+pub fn visualizer(day: u32) -> Option<Visualizer> {
+    VISUALIZERS
+        .iter()
+        .find(|(registered, _)| *registered == day)
+        .map(|(_, visualize)| *visualize)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{visualizer, Animator, Frame, Options, CLEAR};
+    use std::time::Duration;
+
+    #[test]
+    fn frames() {
+        let mut out = Vec::new();
+        let mut animator = Animator::new(
+            &mut out,
+            Options {
+                delay: Duration::ZERO,
+                every: 3,
+            },
+        );
+        let mut rendered = 0;
+        for _ in 0..7 {
+            animator
+                .frame(|| {
+                    rendered += 1;
+                    Frame::Text("x".to_string())
+                })
+                .unwrap();
+        }
+        animator
+            .finish(&Frame::Pixels(vec![vec![true, false], vec![false, true]]))
+            .unwrap();
+
+        // frames 0, 3 and 6, then the final one
+        assert_eq!(rendered, 3);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches(CLEAR).count(), 4);
+        assert!(out.ends_with(&format!("{}\u{2811}\n", CLEAR)));
+    }
+
+    #[test]
+    fn sample() {
+        let mut out = Vec::new();
+        let mut animator = Animator::new(
+            &mut out,
+            Options {
+                delay: Duration::ZERO,
+                every: 10,
+            },
+        );
+        let visualize = visualizer(14).unwrap();
+        visualize(
+            &mut "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9\n".as_bytes(),
+            &mut animator,
+        )
+        .unwrap();
+
+        assert!(visualizer(1).is_none());
+        assert!(String::from_utf8(out).unwrap().contains(CLEAR));
+    }
+}